@@ -1,8 +1,10 @@
+use std::collections::VecDeque;
 use std::ffi::OsString;
 use std::fs;
 use std::io::{IsTerminal, Write};
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::sync::Mutex;
 
 use crate::EXIT_RUNTIME;
 
@@ -71,6 +73,12 @@ else
   git clean -fd
   echo "âœ… Done. '$target_branch' now matches '$resolved'."
 fi
+
+if [[ "${3:-0}" == "1" ]]; then
+  echo "+ submodule update --init --recursive --force"
+  git submodule sync --recursive
+  git submodule update --init --recursive --force
+fi
 "#;
 
 const LIST_GIT_REPOS_SCRIPT: &str = r#"
@@ -97,21 +105,617 @@ find -L "$root" -maxdepth "$git_depth" -mindepth 2 \( -type d -o -type f \) -nam
   | sort -u
 "#;
 
+#[derive(Debug)]
+struct JobOutcome {
+    target: String,
+    exit_code: i32,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+}
+
+fn default_parallelism() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+// Bounded worker pool: each spawned thread pulls the next target off a shared
+// queue until it's empty, so results are collected per-target rather than
+// interleaved on the way out. `flush_job_outcome`/`summarize_job_outcomes`
+// flush each target's buffered stdout/stderr atomically afterward.
+fn run_parallel<F>(jobs: usize, targets: Vec<String>, job: F) -> Vec<JobOutcome>
+where
+    F: Fn(&str) -> JobOutcome + Send + Sync,
+{
+    if targets.is_empty() {
+        return Vec::new();
+    }
+    let worker_count = jobs.max(1).min(targets.len());
+    let queue = Mutex::new(targets.into_iter().collect::<VecDeque<_>>());
+    let results = Mutex::new(Vec::new());
+
+    std::thread::scope(|scope| {
+        for _ in 0..worker_count {
+            scope.spawn(|| {
+                loop {
+                    let next = queue.lock().expect("job queue poisoned").pop_front();
+                    let Some(target) = next else { break };
+                    let outcome = job(&target);
+                    results.lock().expect("job results poisoned").push(outcome);
+                }
+            });
+        }
+    });
+
+    let mut outcomes = results.into_inner().expect("job results poisoned");
+    outcomes.sort_by(|a, b| a.target.cmp(&b.target));
+    outcomes
+}
+
+fn flush_job_outcome(outcome: &JobOutcome) {
+    println!("=== {} (exit {}) ===", outcome.target, outcome.exit_code);
+    if !outcome.stdout.is_empty() {
+        let _ = std::io::stdout().write_all(&outcome.stdout);
+    }
+    if !outcome.stderr.is_empty() {
+        let _ = std::io::stderr().write_all(&outcome.stderr);
+    }
+}
+
+fn summarize_job_outcomes(outcomes: &[JobOutcome]) -> i32 {
+    for outcome in outcomes {
+        flush_job_outcome(outcome);
+    }
+    let failed: Vec<&str> = outcomes
+        .iter()
+        .filter(|outcome| outcome.exit_code != 0)
+        .map(|outcome| outcome.target.as_str())
+        .collect();
+    if failed.is_empty() {
+        println!("summary: {} succeeded", outcomes.len());
+        0
+    } else {
+        eprintln!(
+            "summary: {} succeeded, {} failed: {}",
+            outcomes.len() - failed.len(),
+            failed.len(),
+            failed.join(", ")
+        );
+        EXIT_RUNTIME
+    }
+}
+
+const BUILTIN_SUBCOMMANDS: &[&str] = &[
+    "auth", "create", "exec", "reset", "rm", "list", "tag", "untag", "tunnel",
+];
+
+const ALIAS_CONFIG_ENV: &str = "AGENT_WORKSPACE_CONFIG";
+
+// Mirrors cargo's alias mechanism: a user config file maps short names to
+// full argument lists, so e.g. `agent-workspace rwr` can expand into
+// `reset work-repos --ref origin/main --yes` without a shell wrapper.
+fn alias_config_path() -> PathBuf {
+    if let Ok(value) = std::env::var(ALIAS_CONFIG_ENV)
+        && !value.trim().is_empty()
+    {
+        return PathBuf::from(value);
+    }
+    let home = std::env::var("HOME").unwrap_or_else(|_| String::from("."));
+    PathBuf::from(home)
+        .join(".config")
+        .join("agent-workspace")
+        .join("config.toml")
+}
+
+// An alias value can be written as a single shell-like string
+// (`co = "create --no-work-repos"`) or as an explicit token array
+// (`co = ["create", "--no-work-repos"]`), matching the two forms cargo
+// accepts for its own `[alias]` table.
+fn load_alias(name: &str) -> Option<Vec<String>> {
+    let content = fs::read_to_string(alias_config_path()).ok()?;
+    let document: toml::Value = content.parse().ok()?;
+    let value = document.get("alias").and_then(toml::Value::as_table)?.get(name)?;
+    if let Some(text) = value.as_str() {
+        return Some(text.split_whitespace().map(String::from).collect());
+    }
+    let values = value.as_array()?;
+    Some(
+        values
+            .iter()
+            .filter_map(toml::Value::as_str)
+            .map(String::from)
+            .collect(),
+    )
+}
+
+// Expands `subcommand` through the `[alias]` table until it resolves to a
+// built-in subcommand or the alias lookup comes up empty, splicing each
+// expansion's extra args in front of whatever args followed it. A built-in
+// subcommand name is never looked up as an alias, so an `[alias]` entry can
+// never shadow one. `chain` records the path taken so a cycle can be
+// reported as e.g. `alias loop detected: a -> b -> a`.
+fn expand_alias(subcommand: &str, args: &[OsString]) -> Result<(String, Vec<OsString>), String> {
+    let mut current = subcommand.to_string();
+    let mut rest: Vec<OsString> = args.to_vec();
+    if BUILTIN_SUBCOMMANDS.contains(&current.as_str()) {
+        return Ok((current, rest));
+    }
+    let mut chain = vec![current.clone()];
+    loop {
+        let Some(expansion) = load_alias(&current) else {
+            break;
+        };
+        let Some((head, tail)) = expansion.split_first() else {
+            break;
+        };
+        if chain.contains(head) {
+            chain.push(head.clone());
+            return Err(format!("alias loop detected: {}", chain.join(" -> ")));
+        }
+        let mut new_rest: Vec<OsString> = tail.iter().map(OsString::from).collect();
+        new_rest.extend(rest);
+        rest = new_rest;
+        current = head.clone();
+        chain.push(current.clone());
+        if BUILTIN_SUBCOMMANDS.contains(&current.as_str()) {
+            break;
+        }
+    }
+    Ok((current, rest))
+}
+
+// Standard edit-distance DP: dp[i][j] is the cost to turn a[..i] into b[..j].
+fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut dp = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in dp.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for j in 0..=b.len() {
+        dp[0][j] = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            dp[i][j] = (dp[i - 1][j] + 1)
+                .min(dp[i][j - 1] + 1)
+                .min(dp[i - 1][j - 1] + cost);
+        }
+    }
+    dp[a.len()][b.len()]
+}
+
+// Built-in subcommands plus whatever alias names the user has defined, so
+// a typo'd alias gets suggested just like a typo'd built-in would.
+fn known_subcommand_names() -> Vec<String> {
+    let mut names: Vec<String> = BUILTIN_SUBCOMMANDS.iter().map(|name| name.to_string()).collect();
+    if let Ok(content) = fs::read_to_string(alias_config_path())
+        && let Ok(document) = content.parse::<toml::Value>()
+        && let Some(table) = document.get("alias").and_then(toml::Value::as_table)
+    {
+        names.extend(table.keys().cloned());
+    }
+    names
+}
+
+// Only suggest within roughly a third of the input's length, so e.g.
+// "reset" vs "tunnel" (distance 5) doesn't produce a nonsensical hint.
+fn suggest_subcommand(input: &str) -> Option<String> {
+    let threshold = input.len() / 3 + 1;
+    known_subcommand_names()
+        .into_iter()
+        .map(|name| (levenshtein(input, &name), name))
+        .filter(|(distance, _)| *distance <= threshold)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| name)
+}
+
 pub fn dispatch(subcommand: &str, args: &[OsString]) -> i32 {
-    match subcommand {
-        "auth" => run_auth(args),
-        "create" => run_create(args),
-        "exec" => run_exec(args),
-        "reset" => run_reset(args),
-        "rm" => run_rm(args),
-        "tunnel" => run_tunnel(args),
-        _ => forward(subcommand, args),
+    let (subcommand, args) = match expand_alias(subcommand, args) {
+        Ok(expanded) => expanded,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return EXIT_RUNTIME;
+        }
+    };
+    let (format, args) = extract_message_format(&args);
+    match subcommand.as_str() {
+        "auth" => run_auth(&args),
+        "create" => run_create(&args, format),
+        "exec" => run_exec(&args),
+        "reset" => run_reset(&args),
+        "rm" => run_rm(&args, format),
+        "list" => run_list(&args),
+        "tag" => run_tag(&args),
+        "untag" => run_untag(&args),
+        "tunnel" => run_tunnel(&args),
+        _ => {
+            if let Some(suggestion) = suggest_subcommand(&subcommand) {
+                eprintln!("unknown subcommand '{subcommand}'; did you mean '{suggestion}'?");
+            }
+            forward_with_format(&subcommand, &args, format)
+        }
+    }
+}
+
+// A global flag, accepted anywhere in a subcommand's args the way cargo
+// accepts `--message-format=json` for `build`/`test`/etc. `human` (the
+// default) leaves existing pass-through/streaming behavior untouched;
+// `json` switches `create`/`rm`/unrecognized-subcommand forwarding onto
+// the captured path and emits one NDJSON record per invocation instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum OutputFormat {
+    #[default]
+    Human,
+    Json,
+}
+
+const MESSAGE_FORMAT_FLAG: &str = "--message-format";
+
+// Only scans the args before a literal `--`: everything from `--` onward
+// is the user's own in-container command (`exec`) or other passed-through
+// payload, and must reach its destination byte-for-byte rather than having
+// a look-alike `--message-format` token stripped out of it.
+fn extract_message_format(args: &[OsString]) -> (OutputFormat, Vec<OsString>) {
+    let mut format = OutputFormat::Human;
+    let mut remaining = Vec::with_capacity(args.len());
+    let mut idx = 0;
+    while idx < args.len() {
+        let text = args[idx].to_string_lossy();
+        if text == "--" {
+            remaining.extend(args[idx..].iter().cloned());
+            break;
+        }
+        if let Some(value) = text.strip_prefix("--message-format=") {
+            apply_message_format_value(value, &mut format);
+            idx += 1;
+            continue;
+        }
+        if text == MESSAGE_FORMAT_FLAG {
+            match args.get(idx + 1) {
+                Some(value) => {
+                    apply_message_format_value(&value.to_string_lossy(), &mut format);
+                    idx += 2;
+                }
+                None => {
+                    eprintln!("warn: --message-format requires a value; ignoring");
+                    remaining.push(args[idx].clone());
+                    idx += 1;
+                }
+            }
+            continue;
+        }
+        remaining.push(args[idx].clone());
+        idx += 1;
+    }
+    (format, remaining)
+}
+
+fn apply_message_format_value(value: &str, format: &mut OutputFormat) {
+    match value {
+        "json" => *format = OutputFormat::Json,
+        "human" => *format = OutputFormat::Human,
+        other => eprintln!("warn: unknown --message-format '{other}'; using human"),
+    }
+}
+
+// cargo-platform-style cfg() predicate, parsed from a `[[env]]` entry's
+// `cfg` string: idents (`unix`), key/value equality (`target_os = "linux"`),
+// and the `all`/`any`/`not` combinators.
+#[derive(Debug, Clone)]
+enum CfgExpr {
+    Ident(String),
+    Equal(String, String),
+    All(Vec<CfgExpr>),
+    Any(Vec<CfgExpr>),
+    Not(Box<CfgExpr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum CfgToken {
+    Ident(String),
+    String(String),
+    LeftParen,
+    RightParen,
+    Comma,
+    Equals,
+}
+
+fn tokenize_cfg(input: &str) -> Result<Vec<CfgToken>, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            c if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push(CfgToken::LeftParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(CfgToken::RightParen);
+                i += 1;
+            }
+            ',' => {
+                tokens.push(CfgToken::Comma);
+                i += 1;
+            }
+            '=' => {
+                tokens.push(CfgToken::Equals);
+                i += 1;
+            }
+            '"' => {
+                i += 1;
+                let start = i;
+                while i < chars.len() && chars[i] != '"' {
+                    i += 1;
+                }
+                if i >= chars.len() {
+                    return Err(format!("unterminated string in cfg expression: {input}"));
+                }
+                tokens.push(CfgToken::String(chars[start..i].iter().collect()));
+                i += 1;
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                tokens.push(CfgToken::Ident(chars[start..i].iter().collect()));
+            }
+            other => {
+                return Err(format!(
+                    "unexpected character '{other}' in cfg expression: {input}"
+                ));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct CfgParser<'a> {
+    tokens: &'a [CfgToken],
+    pos: usize,
+}
+
+impl CfgParser<'_> {
+    fn peek(&self) -> Option<&CfgToken> {
+        self.tokens.get(self.pos)
+    }
+
+    fn advance(&mut self) -> Option<&CfgToken> {
+        let token = self.tokens.get(self.pos);
+        self.pos += 1;
+        token
+    }
+
+    fn parse_expr(&mut self) -> Result<CfgExpr, String> {
+        match self.advance().cloned() {
+            Some(CfgToken::Ident(name)) if matches!(name.as_str(), "all" | "any" | "not") => {
+                if self.peek() != Some(&CfgToken::LeftParen) {
+                    return Ok(CfgExpr::Ident(name));
+                }
+                self.advance();
+                let mut children = vec![self.parse_expr()?];
+                while self.peek() == Some(&CfgToken::Comma) {
+                    self.advance();
+                    children.push(self.parse_expr()?);
+                }
+                if self.advance() != Some(&CfgToken::RightParen) {
+                    return Err(format!("expected ')' to close {name}(...)"));
+                }
+                match name.as_str() {
+                    "all" => Ok(CfgExpr::All(children)),
+                    "any" => Ok(CfgExpr::Any(children)),
+                    "not" => {
+                        let mut children = children;
+                        if children.len() != 1 {
+                            return Err(String::from("not(...) takes exactly one expression"));
+                        }
+                        Ok(CfgExpr::Not(Box::new(children.remove(0))))
+                    }
+                    _ => unreachable!(),
+                }
+            }
+            Some(CfgToken::Ident(name)) => {
+                if self.peek() == Some(&CfgToken::Equals) {
+                    self.advance();
+                    match self.advance().cloned() {
+                        Some(CfgToken::String(value)) => Ok(CfgExpr::Equal(name, value)),
+                        _ => Err(String::from("expected a quoted string after '=' in cfg expression")),
+                    }
+                } else {
+                    Ok(CfgExpr::Ident(name))
+                }
+            }
+            other => Err(format!("unexpected token in cfg expression: {other:?}")),
+        }
+    }
+}
+
+fn parse_cfg_expr(input: &str) -> Result<CfgExpr, String> {
+    let tokens = tokenize_cfg(input)?;
+    let mut parser = CfgParser { tokens: &tokens, pos: 0 };
+    let expr = parser.parse_expr()?;
+    if parser.pos != tokens.len() {
+        return Err(format!("trailing tokens in cfg expression: {input}"));
+    }
+    Ok(expr)
+}
+
+struct HostCfg {
+    idents: std::collections::HashSet<&'static str>,
+    keys: std::collections::HashMap<&'static str, &'static str>,
+}
+
+fn host_cfg() -> HostCfg {
+    let mut idents = std::collections::HashSet::new();
+    if cfg!(unix) {
+        idents.insert("unix");
+    }
+    if cfg!(windows) {
+        idents.insert("windows");
+    }
+    let mut keys = std::collections::HashMap::new();
+    keys.insert("target_os", std::env::consts::OS);
+    keys.insert("target_arch", std::env::consts::ARCH);
+    keys.insert("target_family", std::env::consts::FAMILY);
+    HostCfg { idents, keys }
+}
+
+fn eval_cfg_expr(expr: &CfgExpr, host: &HostCfg) -> bool {
+    match expr {
+        CfgExpr::Ident(name) => host.idents.contains(name.as_str()),
+        CfgExpr::Equal(key, value) => host.keys.get(key.as_str()) == Some(&value.as_str()),
+        CfgExpr::All(children) => children.iter().all(|child| eval_cfg_expr(child, host)),
+        CfgExpr::Any(children) => children.iter().any(|child| eval_cfg_expr(child, host)),
+        CfgExpr::Not(child) => !eval_cfg_expr(child, host),
+    }
+}
+
+// Config-driven conditional env injections, read from the same config file
+// as `[alias]`:
+//   [[env]]
+//   cfg = "target_os = \"linux\""
+//   key = "CODEX_SECRET_DIR"
+//   value = "/mnt/linux-secrets"
+// An entry with no `cfg` key always applies. Only entries whose predicate
+// evaluates true against the host are threaded into the forwarded command's
+// environment.
+fn resolve_configured_env_overrides() -> Vec<(String, String)> {
+    let Ok(content) = fs::read_to_string(alias_config_path()) else {
+        return Vec::new();
+    };
+    let Ok(document) = content.parse::<toml::Value>() else {
+        return Vec::new();
+    };
+    let Some(entries) = document.get("env").and_then(toml::Value::as_array) else {
+        return Vec::new();
+    };
+
+    let host = host_cfg();
+    let mut overrides = Vec::new();
+    for entry in entries {
+        let Some(table) = entry.as_table() else {
+            continue;
+        };
+        let (Some(key), Some(value)) = (
+            table.get("key").and_then(toml::Value::as_str),
+            table.get("value").and_then(toml::Value::as_str),
+        ) else {
+            continue;
+        };
+        let matches = match table.get("cfg").and_then(toml::Value::as_str) {
+            Some(predicate) => match parse_cfg_expr(predicate) {
+                Ok(expr) => eval_cfg_expr(&expr, &host),
+                Err(err) => {
+                    eprintln!("warn: ignoring invalid cfg predicate '{predicate}': {err}");
+                    false
+                }
+            },
+            None => true,
+        };
+        if matches {
+            overrides.push((key.to_string(), value.to_string()));
+        }
     }
+    overrides
 }
 
 pub fn forward(subcommand: &str, args: &[OsString]) -> i32 {
+    forward_with_format(subcommand, args, OutputFormat::Human)
+}
+
+// Format-aware variant of `forward`: `human` mode is identical to `forward`
+// (live pass-through/streaming); `json` mode captures the child instead and
+// emits a single NDJSON record built from the resulting `CapturedForward`,
+// for CI/orchestration callers that want to parse results rather than
+// scrape human-formatted text.
+fn forward_with_format(subcommand: &str, args: &[OsString], format: OutputFormat) -> i32 {
     let launcher = resolve_launcher_path();
-    forward_with_launcher_and_env(&launcher, subcommand, args, &[])
+    let overrides = resolve_configured_env_overrides();
+    let overrides_ref: Vec<(&str, &str)> =
+        overrides.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+    match format {
+        OutputFormat::Human => {
+            forward_with_launcher_and_env(&launcher, subcommand, args, &overrides_ref)
+        }
+        OutputFormat::Json => {
+            let captured = match forward_with_launcher_and_env_capture(
+                &launcher,
+                subcommand,
+                args,
+                &overrides_ref,
+            ) {
+                Ok(captured) => captured,
+                Err(err) => {
+                    eprintln!("{err}");
+                    return EXIT_RUNTIME;
+                }
+            };
+            emit_json_result(&launcher, subcommand, args, &overrides, &captured);
+            captured.exit_code
+        }
+    }
+}
+
+fn json_escape(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn json_string_array(values: &[String]) -> String {
+    let items: Vec<String> = values
+        .iter()
+        .map(|value| format!("\"{}\"", json_escape(value)))
+        .collect();
+    format!("[{}]", items.join(","))
+}
+
+// One NDJSON record per forwarded invocation, mirroring cargo's
+// `--message-format=json`: the subcommand and args as forwarded, the
+// resolved launcher path, the env overrides actually applied, and the
+// `CapturedForward` result.
+fn emit_json_result(
+    launcher: &Path,
+    subcommand: &str,
+    args: &[OsString],
+    env_overrides: &[(String, String)],
+    captured: &CapturedForward,
+) {
+    let arg_strings: Vec<String> = args.iter().map(|arg| arg.to_string_lossy().into_owned()).collect();
+    let env_items: Vec<String> = env_overrides
+        .iter()
+        .map(|(key, value)| {
+            format!(
+                "{{\"key\":\"{}\",\"value\":\"{}\"}}",
+                json_escape(key),
+                json_escape(value)
+            )
+        })
+        .collect();
+    println!(
+        "{{\"subcommand\":\"{}\",\"args\":{},\"launcher\":\"{}\",\"env_overrides\":[{}],\"exit_code\":{},\"stdout\":\"{}\",\"stderr\":\"{}\"}}",
+        json_escape(subcommand),
+        json_string_array(&arg_strings),
+        json_escape(&launcher.display().to_string()),
+        env_items.join(","),
+        captured.exit_code,
+        json_escape(&String::from_utf8_lossy(&captured.stdout)),
+        json_escape(&String::from_utf8_lossy(&captured.stderr)),
+    );
 }
 
 #[derive(Debug, Default, Clone)]
@@ -123,6 +727,8 @@ struct ParsedCreate {
     workspace_name: Option<String>,
     primary_repo: Option<String>,
     extra_repos: Vec<String>,
+    from: Option<String>,
+    tags: Vec<String>,
     forwarded_args: Vec<OsString>,
 }
 
@@ -163,6 +769,26 @@ fn parse_create_args(args: &[OsString]) -> Result<ParsedCreate, String> {
                     idx += 1;
                     continue;
                 }
+                "--from" => {
+                    idx += 1;
+                    if idx >= args.len() {
+                        return Err(String::from("missing value for --from"));
+                    }
+                    parsed.from = trimmed_nonempty(args[idx].to_string_lossy().as_ref());
+                    idx += 1;
+                    continue;
+                }
+                "--tag" => {
+                    idx += 1;
+                    if idx >= args.len() {
+                        return Err(String::from("missing value for --tag"));
+                    }
+                    if let Some(tag) = trimmed_nonempty(args[idx].to_string_lossy().as_ref()) {
+                        parsed.tags.push(tag);
+                    }
+                    idx += 1;
+                    continue;
+                }
                 "--name" => {
                     parsed.forwarded_args.push(OsString::from("--name"));
                     idx += 1;
@@ -187,6 +813,18 @@ fn parse_create_args(args: &[OsString]) -> Result<ParsedCreate, String> {
                     idx += 1;
                     continue;
                 }
+                _ if text.starts_with("--from=") => {
+                    parsed.from = trimmed_nonempty(text["--from=".len()..].trim());
+                    idx += 1;
+                    continue;
+                }
+                _ if text.starts_with("--tag=") => {
+                    if let Some(tag) = trimmed_nonempty(text["--tag=".len()..].trim()) {
+                        parsed.tags.push(tag);
+                    }
+                    idx += 1;
+                    continue;
+                }
                 _ if text.starts_with("--name=") => {
                     let value = text["--name=".len()..].trim();
                     let normalized_name = normalize_workspace_name_for_create(value);
@@ -231,79 +869,322 @@ struct CapturedForward {
 
 #[derive(Debug, Clone)]
 struct RepoSpec {
+    host: String,
     owner: String,
     repo: String,
     owner_repo: String,
     clone_url: String,
 }
 
-fn run_create(args: &[OsString]) -> i32 {
-    let parsed = match parse_create_args(args) {
-        Ok(parsed) => parsed,
-        Err(err) => {
-            eprintln!("error: {err}");
-            return EXIT_RUNTIME;
-        }
-    };
-
-    let launcher = resolve_launcher_path();
-    let before = workspace_container_names();
-    let captured = match forward_with_launcher_and_env_capture(
-        &launcher,
-        "create",
-        &parsed.forwarded_args,
-        &[],
-    ) {
-        Ok(captured) => captured,
-        Err(err) => {
-            eprintln!("{err}");
-            return EXIT_RUNTIME;
-        }
-    };
+// Optional forge (GitHub/Forgejo) REST client used to confirm a repo exists
+// and learn its real default branch before cloning or resetting it. Every
+// lookup is best-effort: missing `curl`, no token, a network error, or an
+// unparsable response all degrade silently to the existing shell-based
+// heuristics rather than failing the caller.
+mod forge {
+    use std::process::Command;
 
-    if !captured.stdout.is_empty() {
-        let _ = std::io::stdout().write_all(&captured.stdout);
-        let _ = std::io::stdout().flush();
-    }
-    if !captured.stderr.is_empty() {
-        let _ = std::io::stderr().write_all(&captured.stderr);
-        let _ = std::io::stderr().flush();
+    #[derive(Debug, Clone)]
+    pub struct RepoInfo {
+        pub default_branch: Option<String>,
     }
 
-    if captured.exit_code != 0 || parsed.show_help {
-        return captured.exit_code;
+    fn api_url(host: &str, owner: &str, repo: &str) -> String {
+        if host == "github.com" {
+            format!("https://api.github.com/repos/{owner}/{repo}")
+        } else {
+            format!("https://{host}/api/v1/repos/{owner}/{repo}")
+        }
     }
 
-    if parsed.no_extras || (parsed.private_repo.is_none() && parsed.extra_repos.is_empty()) {
-        return 0;
+    fn forge_token(host: &str) -> Option<String> {
+        let token = if host == "github.com" {
+            std::env::var("GH_TOKEN")
+                .ok()
+                .or_else(|| std::env::var("GITHUB_TOKEN").ok())
+        } else {
+            std::env::var("FORGEJO_TOKEN")
+                .ok()
+                .or_else(|| std::env::var("AGENT_WORKSPACE_FORGE_TOKEN").ok())
+        };
+        token.filter(|value| !value.trim().is_empty())
     }
 
-    let stdout_text = String::from_utf8_lossy(&captured.stdout).to_string();
-    let mut workspace =
-        parse_workspace_name_from_create_output(&stdout_text).filter(|name| !name.is_empty());
-    if workspace.is_none() {
-        workspace = parse_workspace_name_from_json(&stdout_text);
-    }
-    if workspace.is_none() {
-        workspace = detect_new_workspace_name(&before);
-    }
-    if workspace.is_none()
-        && let Some(name) = parsed.workspace_name.as_deref()
-    {
-        let resolved = resolve_workspace_container_name_str(name);
-        if docker_container_exists(&resolved) {
-            workspace = Some(resolved);
+    fn extract_json_string_field(body: &str, field: &str) -> Option<String> {
+        let key = format!("\"{field}\"");
+        let start = body.find(&key)?;
+        let rest = &body[start + key.len()..];
+        let colon = rest.find(':')?;
+        let mut value = rest[colon + 1..].trim_start();
+        if !value.starts_with('"') {
+            return None;
         }
+        value = &value[1..];
+        let end = value.find('"')?;
+        Some(value[..end].to_string())
     }
 
-    let Some(container) = workspace else {
-        eprintln!("warn: unable to detect workspace name; skipping extra repo setup");
-        return 0;
-    };
+    /// Queries the forge's REST API for repo metadata. Returns `None` on
+    /// any failure so callers fall back to shell-based defaults.
+    pub fn lookup_repo(host: &str, owner: &str, repo: &str) -> Option<RepoInfo> {
+        if !super::command_exists("curl") {
+            return None;
+        }
 
-    if let Err(err) = ensure_container_running(&container) {
-        eprintln!("warn: {err}");
-        eprintln!("warn: skipping extra repo setup");
+        let url = api_url(host, owner, repo);
+        let mut cmd = Command::new("curl");
+        let token = forge_token(host)?;
+        cmd.args(["-fsSL", "-H", "Accept: application/json"]);
+        cmd.args(["-H", &format!("Authorization: Bearer {token}")]);
+        cmd.arg(&url);
+
+        let output = cmd.output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let body = String::from_utf8_lossy(&output.stdout).into_owned();
+        let default_branch = extract_json_string_field(&body, "default_branch");
+        if default_branch.is_none() && extract_json_string_field(&body, "full_name").is_none() {
+            return None;
+        }
+        Some(RepoInfo { default_branch })
+    }
+}
+
+fn resolve_forge_default_branch(spec: &RepoSpec) -> Option<String> {
+    forge::lookup_repo(&spec.host, &spec.owner, &spec.repo)?.default_branch
+}
+
+const WORKSPACE_MANIFEST_FILE: &str = ".agent-workspace.toml";
+
+#[derive(Debug, Clone, Default)]
+struct ManifestWorkspace {
+    name: Option<String>,
+    primary_repo: Option<String>,
+    extra_repos: Vec<String>,
+    private_repo: Option<String>,
+    no_extras: bool,
+}
+
+fn discover_workspace_manifest() -> Option<PathBuf> {
+    let candidate = Path::new(WORKSPACE_MANIFEST_FILE);
+    candidate.is_file().then(|| candidate.to_path_buf())
+}
+
+fn parse_manifest_workspace_table(table: &toml::value::Table) -> ManifestWorkspace {
+    ManifestWorkspace {
+        name: table.get("name").and_then(toml::Value::as_str).map(String::from),
+        primary_repo: table
+            .get("primary_repo")
+            .and_then(toml::Value::as_str)
+            .map(String::from),
+        extra_repos: table
+            .get("extra_repos")
+            .and_then(toml::Value::as_array)
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(toml::Value::as_str)
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        private_repo: table
+            .get("private_repo")
+            .and_then(toml::Value::as_str)
+            .map(String::from),
+        no_extras: table
+            .get("no_extras")
+            .and_then(toml::Value::as_bool)
+            .unwrap_or(false),
+    }
+}
+
+// A manifest is either one flat workspace table, or a `[[workspace]]` array
+// defining a whole set to provision in one `create --from` invocation.
+fn load_workspace_manifest(path: &Path) -> Result<Vec<ManifestWorkspace>, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|err| format!("failed to read manifest {}: {err}", path.display()))?;
+    let document: toml::Value = content
+        .parse()
+        .map_err(|err| format!("failed to parse manifest {}: {err}", path.display()))?;
+    let Some(table) = document.as_table() else {
+        return Err(format!("manifest {} is not a TOML table", path.display()));
+    };
+
+    if let Some(entries) = table.get("workspace").and_then(toml::Value::as_array) {
+        return Ok(entries
+            .iter()
+            .filter_map(toml::Value::as_table)
+            .map(parse_manifest_workspace_table)
+            .collect());
+    }
+
+    Ok(vec![parse_manifest_workspace_table(table)])
+}
+
+fn manifest_workspace_to_args(workspace: &ManifestWorkspace) -> Vec<OsString> {
+    let mut args = Vec::new();
+    if let Some(name) = &workspace.name {
+        args.push(OsString::from("--name"));
+        args.push(OsString::from(name));
+    }
+    if workspace.no_extras {
+        args.push(OsString::from("--no-extras"));
+    }
+    if let Some(private_repo) = &workspace.private_repo {
+        args.push(OsString::from("--private-repo"));
+        args.push(OsString::from(private_repo));
+    }
+    if let Some(primary_repo) = &workspace.primary_repo {
+        args.push(OsString::from(primary_repo));
+    }
+    for extra_repo in &workspace.extra_repos {
+        args.push(OsString::from(extra_repo));
+    }
+    args
+}
+
+fn run_create(args: &[OsString], format: OutputFormat) -> i32 {
+    let parsed = match parse_create_args(args) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return EXIT_RUNTIME;
+        }
+    };
+
+    let manifest_path = parsed.from.clone().map(PathBuf::from).or_else(|| {
+        if parsed.primary_repo.is_none()
+            && parsed.extra_repos.is_empty()
+            && parsed.private_repo.is_none()
+            && parsed.workspace_name.is_none()
+            && parsed.tags.is_empty()
+            && !parsed.no_extras
+            && !parsed.no_work_repos
+        {
+            discover_workspace_manifest()
+        } else {
+            None
+        }
+    });
+
+    if let Some(path) = manifest_path {
+        return run_create_from_manifest(&path, format);
+    }
+
+    provision_from_parsed(parsed, format)
+}
+
+fn run_create_from_manifest(path: &Path, format: OutputFormat) -> i32 {
+    let workspaces = match load_workspace_manifest(path) {
+        Ok(workspaces) => workspaces,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return EXIT_RUNTIME;
+        }
+    };
+    if workspaces.is_empty() {
+        eprintln!("error: manifest {} defines no workspaces", path.display());
+        return EXIT_RUNTIME;
+    }
+
+    let mut failed = 0usize;
+    for workspace in &workspaces {
+        let synthetic_args = manifest_workspace_to_args(workspace);
+        let parsed = match parse_create_args(&synthetic_args) {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                eprintln!(
+                    "error: invalid manifest entry ({}): {err}",
+                    workspace.name.as_deref().unwrap_or("<unnamed>")
+                );
+                failed += 1;
+                continue;
+            }
+        };
+        if provision_from_parsed(parsed, format) != 0 {
+            failed += 1;
+        }
+    }
+
+    if failed > 0 { EXIT_RUNTIME } else { 0 }
+}
+
+fn provision_from_parsed(parsed: ParsedCreate, format: OutputFormat) -> i32 {
+    let launcher = resolve_launcher_path();
+    let before = workspace_container_names();
+    let captured = match format {
+        OutputFormat::Human => {
+            forward_with_launcher_and_env_stream(&launcher, "create", &parsed.forwarded_args, &[])
+        }
+        OutputFormat::Json => {
+            forward_with_launcher_and_env_capture(&launcher, "create", &parsed.forwarded_args, &[])
+        }
+    };
+    let captured = match captured {
+        Ok(captured) => captured,
+        Err(err) => {
+            eprintln!("{err}");
+            return EXIT_RUNTIME;
+        }
+    };
+    if format == OutputFormat::Json {
+        emit_json_result(&launcher, "create", &parsed.forwarded_args, &[], &captured);
+    }
+
+    if captured.exit_code != 0 || parsed.show_help {
+        return captured.exit_code;
+    }
+
+    let needs_extra_setup =
+        !parsed.no_extras && (parsed.private_repo.is_some() || !parsed.extra_repos.is_empty());
+
+    if parsed.tags.is_empty() && !needs_extra_setup {
+        return 0;
+    }
+
+    let stdout_text = String::from_utf8_lossy(&captured.stdout).to_string();
+    let mut workspace =
+        parse_workspace_name_from_create_output(&stdout_text).filter(|name| !name.is_empty());
+    if workspace.is_none() {
+        workspace = parse_workspace_name_from_json(&stdout_text);
+    }
+    if workspace.is_none() {
+        workspace = detect_new_workspace_name(&before);
+    }
+    if workspace.is_none()
+        && let Some(name) = parsed.workspace_name.as_deref()
+    {
+        let resolved = resolve_workspace_container_name_str(name);
+        if docker_container_exists(&resolved) {
+            workspace = Some(resolved);
+        }
+    }
+
+    let Some(container) = workspace else {
+        eprintln!("warn: unable to detect workspace name; skipping tag/extra repo setup");
+        return 0;
+    };
+
+    // docker labels can't be attached after the fact, so `--tag` at create
+    // time seeds the same sidecar tag store `agent-workspace tag` writes to
+    // rather than trying to inject a label into the external launcher's
+    // `docker create` invocation.
+    for tag in &parsed.tags {
+        match add_tag(&container, tag) {
+            Ok(()) => println!("tagged {container} with {tag} ({})", tag_label(tag)),
+            Err(err) => eprintln!("warn: failed to tag {container} with {tag}: {err}"),
+        }
+    }
+
+    if !needs_extra_setup {
+        return 0;
+    }
+
+    if let Err(err) = ensure_container_running(&container) {
+        eprintln!("warn: {err}");
+        eprintln!("warn: skipping extra repo setup");
         return 0;
     }
 
@@ -334,30 +1215,264 @@ fn run_create(args: &[OsString]) -> i32 {
 }
 
 fn workspace_container_names() -> Vec<String> {
-    let output = Command::new("docker")
-        .args([
-            "ps",
-            "-a",
-            "--filter",
-            "label=agent-kit.workspace=1",
-            "--format",
-            "{{.Names}}",
-        ])
-        .output();
-    let Ok(output) = output else {
+    resolve_runtime()
+        .list_by_label("agent-kit.workspace=1", true)
+        .unwrap_or_default()
+}
+
+// Same label filter as `workspace_container_names`, but without `-a` so
+// only containers the runtime currently reports as running come back.
+fn running_workspace_container_names() -> Vec<String> {
+    resolve_runtime()
+        .list_by_label("agent-kit.workspace=1", false)
+        .unwrap_or_default()
+}
+
+const TAG_LABEL_PREFIX: &str = "agent-kit.tag.";
+const TAG_STORE_ENV: &str = "AGENT_WORKSPACE_TAG_STORE";
+
+fn tag_label(tag: &str) -> String {
+    format!("{TAG_LABEL_PREFIX}{tag}")
+}
+
+// docker labels are immutable once a container is created, so tags for
+// already-running workspaces live in a local sidecar store keyed by the
+// same label name `create` would have set at container-creation time.
+fn tag_store_path() -> PathBuf {
+    if let Ok(value) = std::env::var(TAG_STORE_ENV)
+        && !value.trim().is_empty()
+    {
+        return PathBuf::from(value);
+    }
+    let home = std::env::var("HOME").unwrap_or_else(|_| String::from("."));
+    PathBuf::from(home)
+        .join(".cache")
+        .join("agent-workspace")
+        .join("tags")
+}
+
+fn read_tag_entries() -> Vec<(String, String)> {
+    let Ok(content) = fs::read_to_string(tag_store_path()) else {
         return Vec::new();
     };
-    if !output.status.success() {
-        return Vec::new();
-    }
-    String::from_utf8_lossy(&output.stdout)
+    content
         .lines()
-        .map(str::trim)
-        .filter(|line| !line.is_empty())
-        .map(ToOwned::to_owned)
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(container, tag)| (container.to_string(), tag.to_string()))
         .collect()
 }
 
+fn write_tag_entries(entries: &[(String, String)]) -> Result<(), String> {
+    let path = tag_store_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|err| format!("failed to create tag store dir {}: {err}", parent.display()))?;
+    }
+    let mut content = String::new();
+    for (container, tag) in entries {
+        content.push_str(container);
+        content.push('\t');
+        content.push_str(tag);
+        content.push('\n');
+    }
+    fs::write(&path, content).map_err(|err| format!("failed to write tag store: {err}"))
+}
+
+fn add_tag(container: &str, tag: &str) -> Result<(), String> {
+    let mut entries = read_tag_entries();
+    if !entries
+        .iter()
+        .any(|(existing_container, existing_tag)| existing_container == container && existing_tag == tag)
+    {
+        entries.push((container.to_string(), tag.to_string()));
+    }
+    write_tag_entries(&entries)
+}
+
+fn remove_tag(container: &str, tag: &str) -> Result<(), String> {
+    let mut entries = read_tag_entries();
+    entries.retain(|(existing_container, existing_tag)| {
+        !(existing_container == container && existing_tag == tag)
+    });
+    write_tag_entries(&entries)
+}
+
+fn containers_for_tag(tag: &str) -> Vec<String> {
+    let known = workspace_container_names();
+    read_tag_entries()
+        .into_iter()
+        .filter(|(_, existing_tag)| existing_tag == tag)
+        .map(|(container, _)| container)
+        .filter(|container| known.iter().any(|name| name == container))
+        .collect()
+}
+
+// Only `--tag` is handled locally; everything else (including plain `list`)
+// still goes through `forward` so the external launcher's own listing
+// format/flags keep working unchanged.
+fn run_list(args: &[OsString]) -> i32 {
+    let mut idx = 0usize;
+    while idx < args.len() {
+        let text = args[idx].to_string_lossy();
+        let tag = if text == "--tag" {
+            idx += 1;
+            if idx >= args.len() {
+                eprintln!("error: missing value for --tag");
+                eprintln!("usage: agent-workspace list [--tag <tag>]");
+                return EXIT_RUNTIME;
+            }
+            Some(args[idx].to_string_lossy().into_owned())
+        } else {
+            text.strip_prefix("--tag=").map(str::to_string)
+        };
+
+        if let Some(tag) = tag {
+            let containers = containers_for_tag(&tag);
+            if containers.is_empty() {
+                eprintln!("warn: no workspace containers tagged: {tag}");
+                return 0;
+            }
+            for container in containers {
+                println!("{container}");
+            }
+            return 0;
+        }
+        idx += 1;
+    }
+    forward("list", args)
+}
+
+#[derive(Debug, Default, Clone)]
+struct ParsedTag {
+    show_help: bool,
+    workspace: Option<String>,
+    action: Option<String>,
+    tag: Option<String>,
+}
+
+// Accepts both `tag <workspace> <tag>` (action defaults to add, kept for
+// back-compat) and `tag <workspace> add|rm <tag>`.
+fn parse_tag_args(args: &[OsString]) -> Result<ParsedTag, String> {
+    let mut parsed = ParsedTag::default();
+    let mut positionals: Vec<String> = Vec::new();
+    for arg in args {
+        let text = arg.to_string_lossy();
+        match text.as_ref() {
+            "-h" | "--help" => parsed.show_help = true,
+            _ if text.starts_with('-') => return Err(format!("unknown option: {text}")),
+            _ => positionals.push(text.into_owned()),
+        }
+    }
+
+    let mut positionals = positionals.into_iter();
+    parsed.workspace = positionals.next();
+    let second = positionals.next();
+    match second.as_deref() {
+        Some("add") | Some("rm") => {
+            parsed.action = second;
+            parsed.tag = positionals.next();
+        }
+        _ => parsed.tag = second,
+    }
+    if let Some(extra) = positionals.next() {
+        return Err(format!("unexpected arg: {extra}"));
+    }
+    Ok(parsed)
+}
+
+fn print_tag_usage() {
+    eprintln!("usage: agent-workspace tag <workspace> [add|rm] <tag>");
+}
+
+fn print_untag_usage() {
+    eprintln!("usage: agent-workspace untag <workspace> <tag>");
+}
+
+fn run_tag(args: &[OsString]) -> i32 {
+    let parsed = match parse_tag_args(args) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("error: {err}");
+            print_tag_usage();
+            return EXIT_RUNTIME;
+        }
+    };
+    if parsed.show_help {
+        print_tag_usage();
+        return 0;
+    }
+    let (Some(workspace), Some(tag)) = (parsed.workspace, parsed.tag) else {
+        eprintln!("error: missing workspace or tag");
+        print_tag_usage();
+        return EXIT_RUNTIME;
+    };
+
+    let container = resolve_workspace_container_name_str(&workspace);
+
+    if parsed.action.as_deref() == Some("rm") {
+        return match remove_tag(&container, &tag) {
+            Ok(()) => {
+                println!("untagged {container} from {tag}");
+                0
+            }
+            Err(err) => {
+                eprintln!("error: {err}");
+                EXIT_RUNTIME
+            }
+        };
+    }
+
+    if !docker_container_exists(&container) {
+        eprintln!("error: workspace container not found: {container}");
+        return EXIT_RUNTIME;
+    }
+
+    match add_tag(&container, &tag) {
+        Ok(()) => {
+            println!("tagged {container} with {tag} ({})", tag_label(&tag));
+            0
+        }
+        Err(err) => {
+            eprintln!("error: {err}");
+            EXIT_RUNTIME
+        }
+    }
+}
+
+// Deprecated alias for `tag <workspace> rm <tag>`, kept so existing scripts
+// that predate the add|rm syntax keep working.
+fn run_untag(args: &[OsString]) -> i32 {
+    let parsed = match parse_tag_args(args) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("error: {err}");
+            print_untag_usage();
+            return EXIT_RUNTIME;
+        }
+    };
+    if parsed.show_help {
+        print_untag_usage();
+        return 0;
+    }
+    let (Some(workspace), Some(tag)) = (parsed.workspace, parsed.tag) else {
+        eprintln!("error: missing workspace or tag");
+        print_untag_usage();
+        return EXIT_RUNTIME;
+    };
+
+    let container = resolve_workspace_container_name_str(&workspace);
+    match remove_tag(&container, &tag) {
+        Ok(()) => {
+            println!("untagged {container} from {tag}");
+            0
+        }
+        Err(err) => {
+            eprintln!("error: {err}");
+            EXIT_RUNTIME
+        }
+    }
+}
+
 fn detect_new_workspace_name(before: &[String]) -> Option<String> {
     let after = workspace_container_names();
     let mut created: Vec<String> = after
@@ -434,6 +1549,7 @@ fn parse_repo_spec(input: &str, default_host: &str) -> Option<RepoSpec> {
     let owner_repo = format!("{owner}/{repo}");
     let clone_url = format!("https://{host}/{owner}/{repo}.git");
     Some(RepoSpec {
+        host,
         owner,
         repo,
         owner_repo,
@@ -479,12 +1595,14 @@ fn run_container_setup_script(container: &str, script: &str, args: &[&str]) -> R
 }
 
 fn setup_private_repo(container: &str, repo: &RepoSpec) -> Result<(), String> {
+    let branch = resolve_forge_default_branch(repo).unwrap_or_default();
     run_container_setup_script(
         container,
         r#"
 set -euo pipefail
 repo_url="${1:?missing repo_url}"
 owner_repo="${2:?missing owner_repo}"
+branch="${3:-}"
 target="$HOME/.private"
 
 if [[ -d "$target/.git" ]]; then
@@ -499,19 +1617,28 @@ if [[ -e "$target" ]]; then
 fi
 
 printf '%s\n' "+ clone ${owner_repo} -> ~/.private"
-GIT_TERMINAL_PROMPT=0 git clone --progress "$repo_url" "$target"
+if [[ -n "$branch" ]]; then
+  GIT_TERMINAL_PROMPT=0 git clone --progress --branch "$branch" "$repo_url" "$target"
+else
+  GIT_TERMINAL_PROMPT=0 git clone --progress "$repo_url" "$target"
+fi
 if [[ ! -L /opt/zsh-kit/.private ]]; then
   rm -rf /opt/zsh-kit/.private || true
   ln -s "$HOME/.private" /opt/zsh-kit/.private || true
 fi
 "#,
-        &[repo.clone_url.as_str(), repo.owner_repo.as_str()],
-    )
+        &[
+            repo.clone_url.as_str(),
+            repo.owner_repo.as_str(),
+            branch.as_str(),
+        ],
+    )
     .map_err(|err| format!("failed to setup ~/.private from {}: {err}", repo.owner_repo))
 }
 
 fn clone_extra_repo(container: &str, repo: &RepoSpec) -> Result<(), String> {
     let destination = format!("/work/{}/{}", repo.owner, repo.repo);
+    let branch = resolve_forge_default_branch(repo).unwrap_or_default();
     run_container_setup_script(
         container,
         r#"
@@ -519,6 +1646,7 @@ set -euo pipefail
 repo_url="${1:?missing repo_url}"
 owner_repo="${2:?missing owner_repo}"
 dest="${3:?missing dest}"
+branch="${4:-}"
 
 if [[ -d "${dest%/}/.git" ]]; then
   printf '%s\n' "repo already present: $dest"
@@ -532,12 +1660,17 @@ fi
 
 printf '%s\n' "+ clone ${owner_repo} -> $dest"
 mkdir -p "$(dirname "$dest")"
-GIT_TERMINAL_PROMPT=0 git clone --progress "$repo_url" "$dest"
+if [[ -n "$branch" ]]; then
+  GIT_TERMINAL_PROMPT=0 git clone --progress --branch "$branch" "$repo_url" "$dest"
+else
+  GIT_TERMINAL_PROMPT=0 git clone --progress "$repo_url" "$dest"
+fi
 "#,
         &[
             repo.clone_url.as_str(),
             repo.owner_repo.as_str(),
             destination.as_str(),
+            branch.as_str(),
         ],
     )
     .map_err(|err| format!("failed to clone extra repo {}: {err}", repo.owner_repo))
@@ -548,6 +1681,7 @@ struct ParsedExec {
     show_help: bool,
     user: Option<OsString>,
     workspace: Option<OsString>,
+    tag: Option<String>,
     command: Vec<OsString>,
 }
 
@@ -556,7 +1690,7 @@ fn parse_exec_args(args: &[OsString]) -> Result<ParsedExec, String> {
     let mut idx = 0usize;
 
     while idx < args.len() {
-        if parsed.workspace.is_some() {
+        if parsed.workspace.is_some() || parsed.tag.is_some() {
             parsed.command.extend(args[idx..].iter().cloned());
             break;
         }
@@ -580,9 +1714,23 @@ fn parse_exec_args(args: &[OsString]) -> Result<ParsedExec, String> {
                 }
                 parsed.user = Some(args[idx].clone());
             }
+            "--tag" => {
+                idx += 1;
+                if idx >= args.len() {
+                    return Err(String::from("missing value for --tag"));
+                }
+                parsed.tag = Some(args[idx].to_string_lossy().into_owned());
+            }
+            "--" if parsed.tag.is_some() => {
+                idx += 1;
+                continue;
+            }
             _ if text.starts_with("--user=") => {
                 parsed.user = Some(OsString::from(&text["--user=".len()..]));
             }
+            _ if text.starts_with("--tag=") => {
+                parsed.tag = Some(text["--tag=".len()..].to_string());
+            }
             _ if text.starts_with('-') => {
                 return Err(format!("unknown option for exec: {text}"));
             }
@@ -593,9 +1741,6 @@ fn parse_exec_args(args: &[OsString]) -> Result<ParsedExec, String> {
         idx += 1;
     }
 
-    if parsed.workspace.is_none() {
-        return Err(String::from("missing workspace name"));
-    }
     Ok(parsed)
 }
 
@@ -614,17 +1759,82 @@ fn run_exec(args: &[OsString]) -> i32 {
         return 0;
     }
 
-    let workspace = parsed.workspace.expect("workspace checked");
-    let workspace = resolve_workspace_container_name(&workspace);
-    let workspace_name = workspace.to_string_lossy().into_owned();
-    if let Err(err) = ensure_container_running(&workspace_name) {
+    if let Some(tag) = parsed.tag.as_deref() {
+        let targets = containers_for_tag(tag);
+        if targets.is_empty() {
+            eprintln!("error: no workspace containers tagged: {tag}");
+            return EXIT_RUNTIME;
+        }
+        let mut exit_code = 0;
+        for container in targets {
+            println!("+ exec --tag {tag} -> {container}");
+            let code = run_exec_in_container(&container, parsed.user.clone(), parsed.command.clone());
+            if code != 0 {
+                exit_code = code;
+            }
+        }
+        return exit_code;
+    }
+
+    let workspace_name = match parsed.workspace {
+        Some(workspace) => resolve_workspace_container_name(&workspace)
+            .to_string_lossy()
+            .into_owned(),
+        None => match pick_workspace_interactively() {
+            Some(name) => name,
+            None => {
+                eprintln!("error: missing workspace name");
+                print_exec_usage();
+                return EXIT_RUNTIME;
+            }
+        },
+    };
+    run_exec_in_container(&workspace_name, parsed.user, parsed.command)
+}
+
+// Stdin/stderr both need to be a TTY: the numbered prompt reads from stdin
+// and writes its menu to stderr, and an external fzf still expects an
+// interactive session to be attached.
+fn interactive_stdio() -> bool {
+    std::io::stdin().is_terminal() && std::io::stderr().is_terminal()
+}
+
+fn pick_workspace_interactively() -> Option<String> {
+    if !interactive_stdio() {
+        return None;
+    }
+    let candidates = list_workspaces().unwrap_or_default();
+    if candidates.is_empty() {
+        return None;
+    }
+    pick_interactively(&candidates)
+}
+
+// Prefers the external `fzf` fuzzy finder when it's on PATH: candidates are
+// piped to its stdin (same plumbing `run_command_capturing_stdin` uses for
+// the gpg export) and the chosen line is read back from its stdout. Falls
+// back to `pick_from`'s numbered prompt otherwise.
+fn pick_interactively(candidates: &[String]) -> Option<String> {
+    if candidates.is_empty() {
+        return None;
+    }
+    if command_exists("fzf") {
+        let input = candidates.join("\n");
+        return match run_command_capturing_stdin(Command::new("fzf"), input.as_bytes()) {
+            Ok((stdout, true)) => trimmed_nonempty(String::from_utf8_lossy(&stdout).as_ref()),
+            _ => None,
+        };
+    }
+    pick_from(candidates)
+}
+
+fn run_exec_in_container(workspace_name: &str, user: Option<OsString>, command: Vec<OsString>) -> i32 {
+    if let Err(err) = ensure_container_running(workspace_name) {
         eprintln!("error: {err}");
         return EXIT_RUNTIME;
     }
 
-    let user = parsed
-        .user
-        .unwrap_or_else(|| OsString::from(DEFAULT_CONTAINER_USER));
+    let user = user.unwrap_or_else(|| OsString::from(DEFAULT_CONTAINER_USER));
 
     let mut cmd = Command::new("docker");
     cmd.arg("exec");
@@ -639,16 +1849,16 @@ fn run_exec(args: &[OsString]) -> i32 {
         cmd.arg("-i");
     }
 
-    if parsed.command.is_empty() {
+    if command.is_empty() {
         cmd.args(["-w", "/work"]);
     }
 
-    cmd.arg(&workspace_name);
+    cmd.arg(workspace_name);
 
-    if parsed.command.is_empty() {
+    if command.is_empty() {
         cmd.args(["zsh", "-l"]);
     } else {
-        cmd.args(parsed.command);
+        cmd.args(command);
     }
 
     match cmd.status() {
@@ -682,18 +1892,41 @@ fn print_tunnel_usage() {
 struct ParsedRm {
     show_help: bool,
     all: bool,
+    tag: Option<String>,
+    jobs: Option<usize>,
     workspace: Option<OsString>,
 }
 
 fn parse_rm_args(args: &[OsString]) -> Result<ParsedRm, String> {
     let mut parsed = ParsedRm::default();
+    let mut idx = 0usize;
 
-    for arg in args {
-        let text = arg.to_string_lossy();
+    while idx < args.len() {
+        let text = args[idx].to_string_lossy();
         match text.as_ref() {
             "-h" | "--help" => parsed.show_help = true,
             "--all" => parsed.all = true,
             "--yes" => {}
+            "--tag" => {
+                idx += 1;
+                if idx >= args.len() {
+                    return Err(String::from("missing value for --tag"));
+                }
+                parsed.tag = Some(args[idx].to_string_lossy().into_owned());
+            }
+            "--jobs" => {
+                idx += 1;
+                if idx >= args.len() {
+                    return Err(String::from("missing value for --jobs"));
+                }
+                parsed.jobs = Some(parse_jobs_value(&args[idx].to_string_lossy())?);
+            }
+            _ if text.starts_with("--tag=") => {
+                parsed.tag = Some(text["--tag=".len()..].to_string());
+            }
+            _ if text.starts_with("--jobs=") => {
+                parsed.jobs = Some(parse_jobs_value(&text["--jobs=".len()..])?);
+            }
             _ if text.starts_with('-') => {
                 return Err(format!("unknown option for rm: {text}"));
             }
@@ -701,15 +1934,23 @@ fn parse_rm_args(args: &[OsString]) -> Result<ParsedRm, String> {
                 if parsed.workspace.is_some() {
                     return Err(String::from("rm accepts at most one workspace name"));
                 }
-                parsed.workspace = Some(arg.clone());
+                parsed.workspace = Some(args[idx].clone());
             }
         }
+        idx += 1;
     }
 
     Ok(parsed)
 }
 
-fn run_rm(args: &[OsString]) -> i32 {
+fn parse_jobs_value(text: &str) -> Result<usize, String> {
+    text.parse::<usize>()
+        .ok()
+        .filter(|value| *value > 0)
+        .ok_or_else(|| format!("--jobs must be a positive integer (got: {text})"))
+}
+
+fn run_rm(args: &[OsString], format: OutputFormat) -> i32 {
     let parsed = match parse_rm_args(args) {
         Ok(parsed) => parsed,
         Err(err) => {
@@ -724,28 +1965,78 @@ fn run_rm(args: &[OsString]) -> i32 {
         return 0;
     }
 
-    if parsed.all {
-        let workspaces = match list_workspaces() {
-            Ok(items) => items,
-            Err(err) => {
-                eprintln!("error: {err}");
+    if parsed.all || parsed.tag.is_some() {
+        let targets = if let Some(tag) = parsed.tag.as_deref() {
+            let targets = containers_for_tag(tag);
+            if targets.is_empty() {
+                eprintln!("error: no workspace containers tagged: {tag}");
                 return EXIT_RUNTIME;
             }
+            targets
+        } else {
+            match list_workspaces() {
+                Ok(items) => items,
+                Err(err) => {
+                    eprintln!("error: {err}");
+                    return EXIT_RUNTIME;
+                }
+            }
         };
-        for workspace in workspaces {
-            let code = forward("rm", &[OsString::from(workspace)]);
-            if code != 0 {
-                return code;
+
+        let jobs = parsed.jobs.unwrap_or_else(default_parallelism);
+        let launcher = resolve_launcher_path();
+        let outcomes = run_parallel(jobs, targets, |workspace| {
+            let workspace_owned = OsString::from(workspace);
+            match forward_with_launcher_and_env_capture(
+                &launcher,
+                "rm",
+                std::slice::from_ref(&workspace_owned),
+                &[],
+            ) {
+                Ok(captured) => {
+                    if format == OutputFormat::Json {
+                        emit_json_result(
+                            &launcher,
+                            "rm",
+                            std::slice::from_ref(&workspace_owned),
+                            &[],
+                            &captured,
+                        );
+                    }
+                    JobOutcome {
+                        target: workspace.to_string(),
+                        exit_code: captured.exit_code,
+                        stdout: captured.stdout,
+                        stderr: captured.stderr,
+                    }
+                }
+                Err(err) => JobOutcome {
+                    target: workspace.to_string(),
+                    exit_code: EXIT_RUNTIME,
+                    stdout: Vec::new(),
+                    stderr: err.into_bytes(),
+                },
             }
-        }
-        return 0;
+        });
+        return match format {
+            OutputFormat::Human => summarize_job_outcomes(&outcomes),
+            OutputFormat::Json => outcomes
+                .iter()
+                .map(|outcome| outcome.exit_code)
+                .find(|&code| code != 0)
+                .unwrap_or(0),
+        };
     }
 
     if let Some(workspace) = parsed.workspace {
-        return forward("rm", &[workspace]);
+        return forward_with_format("rm", &[workspace], format);
+    }
+
+    if let Some(workspace) = pick_workspace_interactively() {
+        return forward_with_format("rm", &[OsString::from(workspace)], format);
     }
 
-    eprintln!("error: missing workspace name or --all");
+    eprintln!("error: missing workspace name, --tag, or --all");
     print_rm_usage();
     EXIT_RUNTIME
 }
@@ -758,6 +2049,11 @@ struct ParsedAuth {
     profile: Option<String>,
     host: Option<String>,
     key: Option<String>,
+    list: bool,
+    remove: bool,
+    all: bool,
+    yes: bool,
+    save_credential: bool,
 }
 
 fn parse_auth_args(args: &[OsString]) -> Result<ParsedAuth, String> {
@@ -768,6 +2064,11 @@ fn parse_auth_args(args: &[OsString]) -> Result<ParsedAuth, String> {
         let current = args[idx].to_string_lossy();
         match current.as_ref() {
             "-h" | "--help" => parsed.show_help = true,
+            "--list" => parsed.list = true,
+            "--remove" => parsed.remove = true,
+            "--all" => parsed.all = true,
+            "--yes" | "-y" => parsed.yes = true,
+            "--save-credential" => parsed.save_credential = true,
             "--container" | "--name" => {
                 idx += 1;
                 if idx >= args.len() {
@@ -856,11 +2157,28 @@ fn run_auth(args: &[OsString]) -> i32 {
         }
     };
 
-    if parsed.show_help || parsed.provider.is_none() {
+    if parsed.show_help {
+        print_auth_usage();
+        return 0;
+    }
+
+    if parsed.list {
+        return run_auth_list();
+    }
+
+    if parsed.remove {
+        return run_auth_remove(&parsed);
+    }
+
+    if parsed.provider.is_none() {
         print_auth_usage();
         return 0;
     }
 
+    if parsed.all {
+        return run_auth_broadcast(&parsed);
+    }
+
     let container = match resolve_container_for_auth(parsed.container.as_deref()) {
         Ok(container) => container,
         Err(err) => {
@@ -871,146 +2189,105 @@ fn run_auth(args: &[OsString]) -> i32 {
 
     let provider = parsed
         .provider
+        .as_deref()
         .expect("provider checked")
         .to_ascii_lowercase();
-    match provider.as_str() {
-        "github" => run_auth_github(&container, parsed.host.as_deref()),
-        "codex" => run_auth_codex(&container, parsed.profile.as_deref()),
-        "gpg" => run_auth_gpg(&container, parsed.key.as_deref()),
+    dispatch_auth_provider(&provider, &container, &parsed)
+}
+
+fn dispatch_auth_provider(provider: &str, container: &str, parsed: &ParsedAuth) -> i32 {
+    match provider {
+        "github" => run_auth_github(container, parsed.host.as_deref(), parsed.save_credential),
+        "codex" => run_auth_codex(container, parsed.profile.as_deref()),
+        "gpg" => run_auth_gpg(container, parsed.key.as_deref()),
+        "ssh" => run_auth_ssh(container, parsed.key.as_deref()),
         _ => {
             eprintln!("error: unknown auth provider: {provider}");
-            eprintln!("hint: expected: codex|github|gpg");
+            eprintln!("hint: expected: codex|github|gpg|ssh");
             EXIT_RUNTIME
         }
     }
 }
 
-fn run_auth_github(container: &str, host: Option<&str>) -> i32 {
-    let gh_host = host
-        .and_then(trimmed_nonempty)
-        .or_else(|| std::env::var("GITHUB_HOST").ok())
-        .filter(|v| !v.trim().is_empty())
-        .unwrap_or_else(|| String::from("github.com"));
-
-    let auth_mode = std::env::var("AGENT_WORKSPACE_AUTH")
-        .ok()
-        .filter(|v| !v.trim().is_empty())
-        .or_else(|| std::env::var("CODEX_WORKSPACE_AUTH").ok())
-        .unwrap_or_else(|| String::from("auto"));
-
-    let env_token = std::env::var("GH_TOKEN")
-        .ok()
-        .filter(|v| !v.trim().is_empty())
-        .or_else(|| {
-            std::env::var("GITHUB_TOKEN")
-                .ok()
-                .filter(|v| !v.trim().is_empty())
-        });
+// Mirrors `reset work-repos`'s discover-many-then-iterate shape: enumerate
+// every running workspace container, show the list for confirmation unless
+// `--yes`, then run the chosen provider against each one, tallying
+// failures so an operator can rotate a token or re-sync a profile across a
+// whole fleet in one command.
+fn run_auth_broadcast(parsed: &ParsedAuth) -> i32 {
+    let provider = parsed
+        .provider
+        .as_deref()
+        .expect("provider checked")
+        .to_ascii_lowercase();
 
-    let keyring_token = if command_exists("gh") {
-        let output = Command::new("gh")
-            .args(["auth", "token", "-h", &gh_host])
-            .env_remove("GH_TOKEN")
-            .env_remove("GITHUB_TOKEN")
-            .output();
-        match output {
-            Ok(result) if result.status.success() => {
-                trimmed_nonempty(String::from_utf8_lossy(&result.stdout).as_ref())
-            }
-            _ => None,
-        }
-    } else {
-        None
-    };
+    let containers = running_workspace_container_names();
+    if containers.is_empty() {
+        eprintln!("warn: no running workspace containers found");
+        return 0;
+    }
 
-    let (chosen_token, chosen_source) = match auth_mode.as_str() {
-        "none" => (None, "none"),
-        "env" => (env_token, "env"),
-        "gh" | "keyring" => {
-            if let Some(token) = keyring_token {
-                (Some(token), "gh")
-            } else {
-                eprintln!(
-                    "warn: AGENT_WORKSPACE_AUTH={auth_mode} but no gh keyring token found; falling back to GH_TOKEN/GITHUB_TOKEN"
-                );
-                (env_token, "env")
-            }
-        }
-        "auto" | "" => {
-            if let Some(token) = keyring_token {
-                (Some(token), "gh")
-            } else {
-                (env_token, "env")
-            }
+    if !parsed.yes {
+        println!(
+            "This will apply '{provider}' auth to {} running workspace(s):",
+            containers.len()
+        );
+        for container in &containers {
+            println!("  - {container}");
         }
-        _ => {
-            eprintln!(
-                "error: unknown AGENT_WORKSPACE_AUTH={auth_mode} (expected: auto|gh|env|none)"
-            );
+        if !confirm_or_abort("Proceed? [y/N] ") {
+            println!("Aborted");
             return EXIT_RUNTIME;
         }
-    };
+    }
 
-    let token = if let Some(token) = chosen_token {
-        token
-    } else {
-        if auth_mode == "none" {
-            eprintln!("error: AGENT_WORKSPACE_AUTH=none; no token to apply");
-        } else {
-            eprintln!("error: no GitHub token found (gh keyring or GH_TOKEN/GITHUB_TOKEN)");
+    let mut failed = 0usize;
+    for container in &containers {
+        if dispatch_auth_provider(&provider, container, parsed) != 0 {
+            eprintln!("error: auth {provider} failed for {container}");
+            failed += 1;
         }
-        eprintln!("hint: run 'gh auth login' or export GH_TOKEN/GITHUB_TOKEN");
-        return EXIT_RUNTIME;
-    };
+    }
 
-    if let Err(err) = ensure_container_running(container) {
-        eprintln!("error: {err}");
+    if failed > 0 {
+        eprintln!("error: auth {provider} failed for {failed} container(s)");
         return EXIT_RUNTIME;
     }
+    0
+}
 
-    println!("auth: github -> {container} ({gh_host}; source={chosen_source})");
-
-    let script = r#"
-set -euo pipefail
-host="${1:-github.com}"
-IFS= read -r token || exit 2
-[[ -n "$token" ]] || exit 2
-
-if command -v gh >/dev/null 2>&1; then
-  printf "%s\n" "$token" | gh auth login --hostname "$host" --with-token >/dev/null 2>&1 || true
-  gh auth setup-git --hostname "$host" --force >/dev/null 2>&1 || gh auth setup-git --hostname "$host" >/dev/null 2>&1 || true
-  gh config set git_protocol https -h "$host" 2>/dev/null || gh config set git_protocol https 2>/dev/null || true
-  exit 0
-fi
-
-if command -v git >/dev/null 2>&1; then
-  token_file="$HOME/.agents-env/gh.token"
-  mkdir -p "${token_file%/*}"
-  printf "%s\n" "$token" >| "$token_file"
-  chmod 600 "$token_file" 2>/dev/null || true
-  git config --global "credential.https://${host}.helper" \
-    "!f() { echo username=x-access-token; echo password=\$(cat \"$token_file\"); }; f"
-fi
-"#;
-
-    let mut cmd = Command::new("docker");
-    cmd.args([
-        "exec",
-        "-i",
-        "-u",
-        DEFAULT_CONTAINER_USER,
-        container,
-        "bash",
-        "-c",
-        script,
-        "--",
-        &gh_host,
-    ]);
+fn run_auth_list() -> i32 {
+    let records = match list_credentials() {
+        Ok(records) => records,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return EXIT_RUNTIME;
+        }
+    };
+    if records.is_empty() {
+        println!("no stored credentials");
+        return 0;
+    }
+    for (provider, host, profile) in records {
+        println!("{provider}\t{host}\t{profile}");
+    }
+    0
+}
 
-    match run_command_with_stdin(cmd, format!("{token}\n").as_bytes(), "update GitHub auth") {
-        Ok(0) => 0,
-        Ok(code) => {
-            eprintln!("error: failed to update GitHub auth in {container} (exit {code})");
+fn run_auth_remove(parsed: &ParsedAuth) -> i32 {
+    let Some(provider) = parsed.provider.as_deref() else {
+        eprintln!("error: --remove requires a provider");
+        return EXIT_RUNTIME;
+    };
+    let host = parsed.host.as_deref().unwrap_or("");
+    let profile = parsed.profile.as_deref().unwrap_or("");
+    match remove_credential(provider, host, profile) {
+        Ok(true) => {
+            println!("removed credential: {provider}/{host}/{profile}");
+            0
+        }
+        Ok(false) => {
+            eprintln!("error: no stored credential: {provider}/{host}/{profile}");
             EXIT_RUNTIME
         }
         Err(err) => {
@@ -1020,39 +2297,796 @@ fi
     }
 }
 
-fn run_auth_codex(container: &str, profile_arg: Option<&str>) -> i32 {
-    let profile = profile_arg
-        .and_then(trimmed_nonempty)
-        .or_else(|| {
-            std::env::var("AGENT_WORKSPACE_CODEX_PROFILE")
-                .ok()
-                .and_then(|v| trimmed_nonempty(&v))
-        })
-        .or_else(|| {
-            std::env::var("CODEX_WORKSPACE_CODEX_PROFILE")
-                .ok()
-                .and_then(|v| trimmed_nonempty(&v))
-        });
+// --- encrypted-at-rest credential store -----------------------------------
+//
+// Records are keyed by (provider, host, profile) and sealed with
+// AES-256-GCM; the passphrase-derived key uses bcrypt-pbkdf with a
+// per-record random salt. Nonces are never reused: every rewrite of a
+// record generates a fresh one. The file starts with a magic/version
+// header so future formats can be detected and rejected cleanly.
+
+const CRED_STORE_MAGIC: &[u8; 4] = b"AWC1";
+const CRED_STORE_ENV: &str = "AGENT_WORKSPACE_CRED_STORE";
+const CRED_PASSPHRASE_ENV: &str = "AGENT_WORKSPACE_CRED_PASSPHRASE";
+const CRED_SALT_LEN: usize = 16;
+const CRED_NONCE_LEN: usize = 12;
+const CRED_PBKDF_ROUNDS: u32 = 16;
 
-    if let Some(profile) = profile {
-        if profile.contains('/')
-            || profile.contains("..")
-            || profile.chars().any(char::is_whitespace)
-        {
-            eprintln!("error: invalid codex profile name: {profile}");
-            return EXIT_RUNTIME;
-        }
+#[derive(Debug, Clone)]
+struct SealedRecord {
+    provider: String,
+    host: String,
+    profile: String,
+    salt: Vec<u8>,
+    nonce: Vec<u8>,
+    ciphertext: Vec<u8>,
+}
 
-        if let Err(err) = ensure_container_running(container) {
-            eprintln!("error: {err}");
-            return EXIT_RUNTIME;
-        }
+fn credential_store_path() -> PathBuf {
+    if let Ok(value) = std::env::var(CRED_STORE_ENV)
+        && !value.trim().is_empty()
+    {
+        return PathBuf::from(value);
+    }
+    let home = std::env::var("HOME").unwrap_or_else(|_| String::from("."));
+    PathBuf::from(home)
+        .join(".config")
+        .join("agent-workspace")
+        .join("credentials")
+}
 
-        let script = r#"
-profile="${1:?missing profile}"
-if ! typeset -f codex-use >/dev/null 2>&1; then
-  for source_file in \
-    /opt/zsh-kit/scripts/_features/agent-workspace/workspace-launcher.zsh \
+fn write_length_prefixed(buffer: &mut Vec<u8>, bytes: &[u8]) {
+    buffer.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    buffer.extend_from_slice(bytes);
+}
+
+fn read_length_prefixed(buffer: &[u8], cursor: &mut usize) -> Option<Vec<u8>> {
+    let len_bytes = buffer.get(*cursor..*cursor + 4)?;
+    let len = u32::from_le_bytes(len_bytes.try_into().ok()?) as usize;
+    *cursor += 4;
+    let bytes = buffer.get(*cursor..*cursor + len)?.to_vec();
+    *cursor += len;
+    Some(bytes)
+}
+
+fn read_credential_records() -> Result<Vec<SealedRecord>, String> {
+    let path = credential_store_path();
+    let Ok(buffer) = fs::read(&path) else {
+        return Ok(Vec::new());
+    };
+    if buffer.len() < CRED_STORE_MAGIC.len() || &buffer[..CRED_STORE_MAGIC.len()] != CRED_STORE_MAGIC {
+        return Err(format!("unrecognized credential store format: {}", path.display()));
+    }
+
+    let mut cursor = CRED_STORE_MAGIC.len();
+    let mut records = Vec::new();
+    while cursor < buffer.len() {
+        let provider = read_length_prefixed(&buffer, &mut cursor)
+            .ok_or_else(|| String::from("corrupt credential store: truncated provider"))?;
+        let host = read_length_prefixed(&buffer, &mut cursor)
+            .ok_or_else(|| String::from("corrupt credential store: truncated host"))?;
+        let profile = read_length_prefixed(&buffer, &mut cursor)
+            .ok_or_else(|| String::from("corrupt credential store: truncated profile"))?;
+        let salt = read_length_prefixed(&buffer, &mut cursor)
+            .ok_or_else(|| String::from("corrupt credential store: truncated salt"))?;
+        let nonce = read_length_prefixed(&buffer, &mut cursor)
+            .ok_or_else(|| String::from("corrupt credential store: truncated nonce"))?;
+        let ciphertext = read_length_prefixed(&buffer, &mut cursor)
+            .ok_or_else(|| String::from("corrupt credential store: truncated ciphertext"))?;
+        records.push(SealedRecord {
+            provider: String::from_utf8_lossy(&provider).into_owned(),
+            host: String::from_utf8_lossy(&host).into_owned(),
+            profile: String::from_utf8_lossy(&profile).into_owned(),
+            salt,
+            nonce,
+            ciphertext,
+        });
+    }
+    Ok(records)
+}
+
+fn write_credential_records(records: &[SealedRecord]) -> Result<(), String> {
+    let path = credential_store_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|err| format!("failed to create credential store dir {}: {err}", parent.display()))?;
+    }
+
+    let mut buffer = Vec::new();
+    buffer.extend_from_slice(CRED_STORE_MAGIC);
+    for record in records {
+        write_length_prefixed(&mut buffer, record.provider.as_bytes());
+        write_length_prefixed(&mut buffer, record.host.as_bytes());
+        write_length_prefixed(&mut buffer, record.profile.as_bytes());
+        write_length_prefixed(&mut buffer, &record.salt);
+        write_length_prefixed(&mut buffer, &record.nonce);
+        write_length_prefixed(&mut buffer, &record.ciphertext);
+    }
+
+    fs::write(&path, buffer).map_err(|err| format!("failed to write credential store: {err}"))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Ok(metadata) = fs::metadata(&path) {
+            let mut permissions = metadata.permissions();
+            permissions.set_mode(0o600);
+            let _ = fs::set_permissions(&path, permissions);
+        }
+    }
+    Ok(())
+}
+
+fn resolve_store_passphrase() -> Result<String, String> {
+    if let Ok(value) = std::env::var(CRED_PASSPHRASE_ENV)
+        && !value.trim().is_empty()
+    {
+        return Ok(value);
+    }
+    if !std::io::stdin().is_terminal() {
+        return Err(format!(
+            "no passphrase available; set {CRED_PASSPHRASE_ENV} or run interactively"
+        ));
+    }
+    eprint!("credential store passphrase: ");
+    let _ = std::io::stderr().flush();
+    let mut input = String::new();
+    std::io::stdin()
+        .read_line(&mut input)
+        .map_err(|err| format!("failed to read passphrase: {err}"))?;
+    let passphrase = input.trim_end_matches(['\n', '\r']).to_string();
+    if passphrase.is_empty() {
+        return Err(String::from("empty passphrase"));
+    }
+    Ok(passphrase)
+}
+
+fn derive_credential_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+    let mut key = [0u8; 32];
+    bcrypt_pbkdf::bcrypt_pbkdf(passphrase.as_bytes(), salt, CRED_PBKDF_ROUNDS, &mut key)
+        .map_err(|err| format!("key derivation failed: {err}"))?;
+    Ok(key)
+}
+
+fn seal_credential_bytes(passphrase: &str, plaintext: &[u8]) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>), String> {
+    use aes_gcm::aead::{Aead, KeyInit, OsRng};
+    use aes_gcm::{Aes256Gcm, Nonce};
+
+    let mut salt = vec![0u8; CRED_SALT_LEN];
+    aes_gcm::aead::rand_core::RngCore::fill_bytes(&mut OsRng, &mut salt);
+    let key = derive_credential_key(passphrase, &salt)?;
+
+    let mut nonce_bytes = vec![0u8; CRED_NONCE_LEN];
+    aes_gcm::aead::rand_core::RngCore::fill_bytes(&mut OsRng, &mut nonce_bytes);
+
+    let cipher = Aes256Gcm::new_from_slice(&key).map_err(|err| format!("failed to init cipher: {err}"))?;
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce_bytes), plaintext)
+        .map_err(|_| String::from("encryption failed"))?;
+    Ok((salt, nonce_bytes, ciphertext))
+}
+
+fn open_credential_bytes(passphrase: &str, record: &SealedRecord) -> Result<Vec<u8>, String> {
+    use aes_gcm::aead::{Aead, KeyInit};
+    use aes_gcm::{Aes256Gcm, Nonce};
+
+    let key = derive_credential_key(passphrase, &record.salt)?;
+    let cipher = Aes256Gcm::new_from_slice(&key).map_err(|err| format!("failed to init cipher: {err}"))?;
+    cipher
+        .decrypt(Nonce::from_slice(&record.nonce), record.ciphertext.as_slice())
+        .map_err(|_| String::from("decryption failed: wrong passphrase or corrupted record"))
+}
+
+fn store_credential(provider: &str, host: &str, profile: &str, plaintext: &[u8]) -> Result<(), String> {
+    let passphrase = resolve_store_passphrase()?;
+    let (salt, nonce, ciphertext) = seal_credential_bytes(&passphrase, plaintext)?;
+
+    let mut records = read_credential_records()?;
+    records.retain(|record| {
+        !(record.provider == provider && record.host == host && record.profile == profile)
+    });
+    records.push(SealedRecord {
+        provider: provider.to_string(),
+        host: host.to_string(),
+        profile: profile.to_string(),
+        salt,
+        nonce,
+        ciphertext,
+    });
+    write_credential_records(&records)
+}
+
+fn load_credential(provider: &str, host: &str, profile: &str) -> Result<Option<Vec<u8>>, String> {
+    let records = read_credential_records()?;
+    let Some(record) = records
+        .iter()
+        .find(|record| record.provider == provider && record.host == host && record.profile == profile)
+    else {
+        return Ok(None);
+    };
+    let passphrase = resolve_store_passphrase()?;
+    open_credential_bytes(&passphrase, record).map(Some)
+}
+
+fn remove_credential(provider: &str, host: &str, profile: &str) -> Result<bool, String> {
+    let mut records = read_credential_records()?;
+    let before = records.len();
+    records.retain(|record| {
+        !(record.provider == provider && record.host == host && record.profile == profile)
+    });
+    let removed = records.len() != before;
+    if removed {
+        write_credential_records(&records)?;
+    }
+    Ok(removed)
+}
+
+fn list_credentials() -> Result<Vec<(String, String, String)>, String> {
+    Ok(read_credential_records()?
+        .into_iter()
+        .map(|record| (record.provider, record.host, record.profile))
+        .collect())
+}
+
+// Abstracts the local container *engine* (docker vs podman) so the docker
+// binary isn't hardcoded at every discovery/reset call site. This is a
+// different axis from `ContainerTransport` below: that trait picks local
+// vs remote (docker vs kubectl), while this one picks which local CLI
+// `DockerTransport` shells out to. Podman's CLI mirrors docker's closely
+// enough that both engines share one default implementation keyed only by
+// binary name. Selected via AGENT_WORKSPACE_RUNTIME, falling back to
+// whichever binary is found on PATH via `command_exists`.
+const RUNTIME_ENV: &str = "AGENT_WORKSPACE_RUNTIME";
+
+trait ContainerRuntime {
+    fn binary(&self) -> &'static str;
+
+    fn container_exists(&self, name: &str) -> bool {
+        Command::new(self.binary())
+            .args(["container", "inspect", name])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false)
+    }
+
+    fn inspect_running(&self, container: &str) -> Result<bool, String> {
+        let output = Command::new(self.binary())
+            .args(["inspect", "-f", "{{.State.Running}}", container])
+            .output()
+            .map_err(|err| format!("failed to inspect {container}: {err}"))?;
+        if !output.status.success() {
+            return Ok(false);
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim() == "true")
+    }
+
+    fn start(&self, container: &str) -> Result<(), String> {
+        let status = Command::new(self.binary())
+            .args(["start", container])
+            .status()
+            .map_err(|err| format!("failed to start workspace {container}: {err}"))?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "{} start failed for {container} (exit {})",
+                self.binary(),
+                status.code().unwrap_or(EXIT_RUNTIME)
+            ))
+        }
+    }
+
+    fn list_by_label(&self, label: &str, all: bool) -> Result<Vec<String>, String> {
+        let filter = format!("label={label}");
+        let mut args = vec!["ps"];
+        if all {
+            args.push("-a");
+        }
+        args.extend(["--filter", &filter, "--format", "{{.Names}}"]);
+        let output = Command::new(self.binary())
+            .args(&args)
+            .output()
+            .map_err(|err| format!("failed to list containers via {}: {err}", self.binary()))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            return Err(format!(
+                "{} ps failed (exit {}): {stderr}",
+                self.binary(),
+                output.status.code().unwrap_or(EXIT_RUNTIME)
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_owned)
+            .collect())
+    }
+
+    fn exec(&self, container: &str, user: Option<&str>, argv: &[&str]) -> Result<i32, String> {
+        let mut cmd = Command::new(self.binary());
+        cmd.arg("exec");
+        if let Some(user) = user {
+            cmd.args(["-u", user]);
+        }
+        cmd.arg(container);
+        cmd.args(argv);
+        let status = cmd
+            .status()
+            .map_err(|err| format!("failed to exec in {container}: {err}"))?;
+        Ok(status.code().unwrap_or(EXIT_RUNTIME))
+    }
+
+    fn exec_with_stdin(
+        &self,
+        container: &str,
+        user: Option<&str>,
+        argv: &[&str],
+        input: &[u8],
+    ) -> Result<i32, String> {
+        let mut cmd = Command::new(self.binary());
+        cmd.arg("exec").arg("-i");
+        if let Some(user) = user {
+            cmd.args(["-u", user]);
+        }
+        cmd.arg(container);
+        cmd.args(argv);
+        run_command_with_stdin(cmd, input, &format!("exec in {container}"))
+    }
+
+    fn exec_capture(&self, container: &str, user: Option<&str>, argv: &[&str]) -> (i32, Vec<u8>, Vec<u8>) {
+        let mut cmd = Command::new(self.binary());
+        cmd.arg("exec").arg("-i");
+        if let Some(user) = user {
+            cmd.args(["-u", user]);
+        }
+        cmd.arg(container);
+        cmd.args(argv);
+        match cmd.output() {
+            Ok(result) => (
+                result.status.code().unwrap_or(EXIT_RUNTIME),
+                result.stdout,
+                result.stderr,
+            ),
+            Err(err) => (
+                EXIT_RUNTIME,
+                Vec::new(),
+                format!("failed to exec in {container}: {err}").into_bytes(),
+            ),
+        }
+    }
+}
+
+struct DockerRuntime;
+
+impl ContainerRuntime for DockerRuntime {
+    fn binary(&self) -> &'static str {
+        "docker"
+    }
+}
+
+struct PodmanRuntime;
+
+impl ContainerRuntime for PodmanRuntime {
+    fn binary(&self) -> &'static str {
+        "podman"
+    }
+}
+
+// Honors an explicit AGENT_WORKSPACE_RUNTIME override; otherwise prefers
+// docker (the long-standing default) and falls back to podman when only
+// podman is on PATH, so rootless-podman-only hosts work without any config.
+fn resolve_runtime() -> Box<dyn ContainerRuntime> {
+    match std::env::var(RUNTIME_ENV).ok().as_deref() {
+        Some("podman") => Box::new(PodmanRuntime),
+        Some("docker") => Box::new(DockerRuntime),
+        Some(other) if !other.trim().is_empty() => {
+            eprintln!("warn: unknown {RUNTIME_ENV}={other}; auto-detecting instead");
+            if command_exists("docker") {
+                Box::new(DockerRuntime)
+            } else {
+                Box::new(PodmanRuntime)
+            }
+        }
+        _ => {
+            if command_exists("docker") {
+                Box::new(DockerRuntime)
+            } else if command_exists("podman") {
+                Box::new(PodmanRuntime)
+            } else {
+                Box::new(DockerRuntime)
+            }
+        }
+    }
+}
+
+// Abstracts "run a command inside a workspace" so auth/reset flows can
+// target either a local docker container or a remote Kubernetes pod without
+// branching at every call site. Selected via AGENT_WORKSPACE_TRANSPORT.
+const TRANSPORT_ENV: &str = "AGENT_WORKSPACE_TRANSPORT";
+
+trait ContainerTransport {
+    fn running(&self, container: &str) -> Result<bool, String>;
+    fn exec(&self, container: &str, user: Option<&str>, argv: &[&str]) -> Result<i32, String>;
+    fn exec_with_stdin(
+        &self,
+        container: &str,
+        user: Option<&str>,
+        argv: &[&str],
+        input: &[u8],
+    ) -> Result<i32, String>;
+    fn exec_capture(&self, container: &str, user: Option<&str>, argv: &[&str]) -> (i32, Vec<u8>, Vec<u8>);
+}
+
+struct DockerTransport;
+
+impl ContainerTransport for DockerTransport {
+    fn running(&self, container: &str) -> Result<bool, String> {
+        resolve_runtime().inspect_running(container)
+    }
+
+    fn exec(&self, container: &str, user: Option<&str>, argv: &[&str]) -> Result<i32, String> {
+        resolve_runtime().exec(container, user, argv)
+    }
+
+    fn exec_with_stdin(
+        &self,
+        container: &str,
+        user: Option<&str>,
+        argv: &[&str],
+        input: &[u8],
+    ) -> Result<i32, String> {
+        resolve_runtime().exec_with_stdin(container, user, argv, input)
+    }
+
+    fn exec_capture(&self, container: &str, user: Option<&str>, argv: &[&str]) -> (i32, Vec<u8>, Vec<u8>) {
+        resolve_runtime().exec_capture(container, user, argv)
+    }
+}
+
+// Resolves workspace names to pods via the same `agent-kit.workspace=1`
+// label convention docker containers carry, matched against a per-workspace
+// `agent-kit.name=<container>` label. kubectl has no equivalent of `-u`, so
+// `user` is ignored here; pods are expected to already run as the right
+// user via their security context.
+struct KubectlTransport;
+
+impl KubectlTransport {
+    fn resolve_pod(&self, container: &str) -> Result<String, String> {
+        let output = Command::new("kubectl")
+            .args([
+                "get",
+                "pods",
+                "-l",
+                &format!("agent-kit.workspace=1,agent-kit.name={container}"),
+                "-o",
+                "jsonpath={.items[0].metadata.name}",
+            ])
+            .output()
+            .map_err(|err| format!("failed to resolve pod for {container}: {err}"))?;
+        if !output.status.success() {
+            return Err(format!("kubectl get pods failed for workspace {container}"));
+        }
+        let pod = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if pod.is_empty() {
+            return Err(format!(
+                "no pod found for workspace {container} (label agent-kit.name={container})"
+            ));
+        }
+        Ok(pod)
+    }
+}
+
+impl ContainerTransport for KubectlTransport {
+    fn running(&self, container: &str) -> Result<bool, String> {
+        let pod = self.resolve_pod(container)?;
+        let output = Command::new("kubectl")
+            .args(["get", "pod", &pod, "-o", "jsonpath={.status.phase}"])
+            .output()
+            .map_err(|err| format!("failed to inspect pod {pod}: {err}"))?;
+        Ok(output.status.success() && String::from_utf8_lossy(&output.stdout).trim() == "Running")
+    }
+
+    fn exec(&self, container: &str, _user: Option<&str>, argv: &[&str]) -> Result<i32, String> {
+        let pod = self.resolve_pod(container)?;
+        let mut cmd = Command::new("kubectl");
+        cmd.args(["exec", &pod, "-c", container, "--"]);
+        cmd.args(argv);
+        let status = cmd
+            .status()
+            .map_err(|err| format!("failed to exec in pod {pod}: {err}"))?;
+        Ok(status.code().unwrap_or(EXIT_RUNTIME))
+    }
+
+    fn exec_with_stdin(
+        &self,
+        container: &str,
+        _user: Option<&str>,
+        argv: &[&str],
+        input: &[u8],
+    ) -> Result<i32, String> {
+        let pod = self.resolve_pod(container)?;
+        let mut cmd = Command::new("kubectl");
+        cmd.args(["exec", "-i", &pod, "-c", container, "--"]);
+        cmd.args(argv);
+        run_command_with_stdin(cmd, input, &format!("exec in pod {pod}"))
+    }
+
+    fn exec_capture(&self, container: &str, _user: Option<&str>, argv: &[&str]) -> (i32, Vec<u8>, Vec<u8>) {
+        let pod = match self.resolve_pod(container) {
+            Ok(pod) => pod,
+            Err(err) => return (EXIT_RUNTIME, Vec::new(), err.into_bytes()),
+        };
+        let mut cmd = Command::new("kubectl");
+        cmd.args(["exec", "-i", &pod, "-c", container, "--"]);
+        cmd.args(argv);
+        match cmd.output() {
+            Ok(result) => (
+                result.status.code().unwrap_or(EXIT_RUNTIME),
+                result.stdout,
+                result.stderr,
+            ),
+            Err(err) => (
+                EXIT_RUNTIME,
+                Vec::new(),
+                format!("failed to exec in pod {pod}: {err}").into_bytes(),
+            ),
+        }
+    }
+}
+
+fn is_kubectl_transport() -> bool {
+    matches!(
+        std::env::var(TRANSPORT_ENV).ok().as_deref(),
+        Some("kubectl") | Some("kubernetes") | Some("k8s")
+    )
+}
+
+fn resolve_transport() -> Box<dyn ContainerTransport> {
+    if is_kubectl_transport() {
+        Box::new(KubectlTransport)
+    } else {
+        Box::new(DockerTransport)
+    }
+}
+
+const GH_TOKEN_TARGET_SNIPPET: &str = "target=\"$HOME/.agents-env/gh.token\"";
+const GH_TOKEN_ENC_TARGET_SNIPPET: &str = "target=\"$HOME/.agents-env/gh.token.enc\"";
+
+fn run_auth_github(container: &str, host: Option<&str>, save_credential: bool) -> i32 {
+    let gh_host = host
+        .and_then(trimmed_nonempty)
+        .or_else(|| std::env::var("GITHUB_HOST").ok())
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| String::from("github.com"));
+
+    let auth_mode = std::env::var("AGENT_WORKSPACE_AUTH")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .or_else(|| std::env::var("CODEX_WORKSPACE_AUTH").ok())
+        .unwrap_or_else(|| String::from("auto"));
+
+    let env_token = std::env::var("GH_TOKEN")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .or_else(|| {
+            std::env::var("GITHUB_TOKEN")
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+        });
+
+    let keyring_token = if command_exists("gh") {
+        let output = Command::new("gh")
+            .args(["auth", "token", "-h", &gh_host])
+            .env_remove("GH_TOKEN")
+            .env_remove("GITHUB_TOKEN")
+            .output();
+        match output {
+            Ok(result) if result.status.success() => {
+                trimmed_nonempty(String::from_utf8_lossy(&result.stdout).as_ref())
+            }
+            _ => None,
+        }
+    } else {
+        None
+    };
+
+    let (chosen_token, mut chosen_source) = match auth_mode.as_str() {
+        "none" => (None, "none"),
+        "env" => (env_token, "env"),
+        "gh" | "keyring" => {
+            if let Some(token) = keyring_token {
+                (Some(token), "gh")
+            } else {
+                eprintln!(
+                    "warn: AGENT_WORKSPACE_AUTH={auth_mode} but no gh keyring token found; falling back to GH_TOKEN/GITHUB_TOKEN"
+                );
+                (env_token, "env")
+            }
+        }
+        "auto" | "" => {
+            if let Some(token) = keyring_token {
+                (Some(token), "gh")
+            } else {
+                (env_token, "env")
+            }
+        }
+        _ => {
+            eprintln!(
+                "error: unknown AGENT_WORKSPACE_AUTH={auth_mode} (expected: auto|gh|env|none)"
+            );
+            return EXIT_RUNTIME;
+        }
+    };
+
+    let token = if let Some(token) = chosen_token {
+        token
+    } else if auth_mode != "none" {
+        match load_credential("github", &gh_host, "") {
+            Ok(Some(bytes)) => {
+                chosen_source = "store";
+                String::from_utf8_lossy(&bytes).into_owned()
+            }
+            Ok(None) => {
+                eprintln!("error: no GitHub token found (gh keyring, GH_TOKEN/GITHUB_TOKEN, or saved credential)");
+                eprintln!("hint: run 'gh auth login' or export GH_TOKEN/GITHUB_TOKEN");
+                return EXIT_RUNTIME;
+            }
+            Err(err) => {
+                eprintln!("error: failed to load saved github credential: {err}");
+                return EXIT_RUNTIME;
+            }
+        }
+    } else {
+        eprintln!("error: AGENT_WORKSPACE_AUTH=none; no token to apply");
+        eprintln!("hint: run 'gh auth login' or export GH_TOKEN/GITHUB_TOKEN");
+        return EXIT_RUNTIME;
+    };
+
+    if let Err(err) = ensure_container_running(container) {
+        eprintln!("error: {err}");
+        return EXIT_RUNTIME;
+    }
+
+    println!("auth: github -> {container} ({gh_host}; source={chosen_source})");
+
+    let transport = resolve_transport();
+    let probe_script = r#"
+if command -v gh >/dev/null 2>&1; then
+  echo gh
+elif command -v git >/dev/null 2>&1; then
+  echo git
+else
+  echo none
+fi
+"#;
+    let (_, probe_stdout, _) = transport.exec_capture(
+        container,
+        Some(DEFAULT_CONTAINER_USER),
+        &["bash", "-c", probe_script],
+    );
+
+    let result = match String::from_utf8_lossy(&probe_stdout).trim() {
+        "gh" => {
+            let script = r#"
+set -euo pipefail
+host="${1:-github.com}"
+IFS= read -r token || exit 2
+[[ -n "$token" ]] || exit 2
+printf "%s\n" "$token" | gh auth login --hostname "$host" --with-token >/dev/null 2>&1 || true
+gh auth setup-git --hostname "$host" --force >/dev/null 2>&1 || gh auth setup-git --hostname "$host" >/dev/null 2>&1 || true
+gh config set git_protocol https -h "$host" 2>/dev/null || gh config set git_protocol https 2>/dev/null || true
+"#;
+            transport.exec_with_stdin(
+                container,
+                Some(DEFAULT_CONTAINER_USER),
+                &["bash", "-c", script, "--", &gh_host],
+                format!("{token}\n").as_bytes(),
+            )
+        }
+        "git" if encryption_enabled() => (|| -> Result<i32, String> {
+            let passphrase = resolve_store_passphrase()?;
+            let envelope = encrypt_secret_payload(&passphrase, format!("{token}\n").as_bytes())?;
+            stage_decrypt_shim(container)?;
+            let skipped = sync_file_with_integrity(container, GH_TOKEN_ENC_TARGET_SNIPPET, &envelope)?;
+            if skipped {
+                println!("auth: github token (encrypted) unchanged in {container}");
+            }
+            let helper_script = r#"
+set -euo pipefail
+host="${1:?missing host}"
+token_file="$HOME/.agents-env/gh.token.enc"
+git config --global "credential.https://${host}.helper" \
+  "!f() { echo username=x-access-token; echo password=\$(python3 \"\$HOME/.agents-env/decrypt-secret.py\" \"$token_file\" 2>/dev/null); }; f"
+"#;
+            transport.exec(
+                container,
+                Some(DEFAULT_CONTAINER_USER),
+                &["bash", "-c", helper_script, "--", &gh_host],
+            )
+        })(),
+        "git" => match sync_file_with_integrity(
+            container,
+            GH_TOKEN_TARGET_SNIPPET,
+            format!("{token}\n").as_bytes(),
+        ) {
+            Ok(skipped) => {
+                if skipped {
+                    println!("auth: github token unchanged in {container}");
+                }
+                let helper_script = r#"
+set -euo pipefail
+host="${1:?missing host}"
+token_file="$HOME/.agents-env/gh.token"
+git config --global "credential.https://${host}.helper" \
+  "!f() { echo username=x-access-token; echo password=\$(cat \"$token_file\"); }; f"
+"#;
+                transport.exec(
+                    container,
+                    Some(DEFAULT_CONTAINER_USER),
+                    &["bash", "-c", helper_script, "--", &gh_host],
+                )
+            }
+            Err(err) => Err(err),
+        },
+        _ => Ok(0),
+    };
+
+    match result {
+        Ok(0) => {
+            if save_credential {
+                if let Err(err) = store_credential("github", &gh_host, "", token.as_bytes()) {
+                    eprintln!("warn: failed to persist github credential: {err}");
+                }
+            }
+            0
+        }
+        Ok(code) => {
+            eprintln!("error: failed to update GitHub auth in {container} (exit {code})");
+            EXIT_RUNTIME
+        }
+        Err(err) => {
+            eprintln!("error: {err}");
+            EXIT_RUNTIME
+        }
+    }
+}
+
+fn run_auth_codex(container: &str, profile_arg: Option<&str>) -> i32 {
+    let profile = profile_arg
+        .and_then(trimmed_nonempty)
+        .or_else(|| {
+            std::env::var("AGENT_WORKSPACE_CODEX_PROFILE")
+                .ok()
+                .and_then(|v| trimmed_nonempty(&v))
+        })
+        .or_else(|| {
+            std::env::var("CODEX_WORKSPACE_CODEX_PROFILE")
+                .ok()
+                .and_then(|v| trimmed_nonempty(&v))
+        });
+
+    if let Some(profile) = profile {
+        if profile.contains('/')
+            || profile.contains("..")
+            || profile.chars().any(char::is_whitespace)
+        {
+            eprintln!("error: invalid codex profile name: {profile}");
+            return EXIT_RUNTIME;
+        }
+
+        if let Err(err) = ensure_container_running(container) {
+            eprintln!("error: {err}");
+            return EXIT_RUNTIME;
+        }
+
+        let script = r#"
+profile="${1:?missing profile}"
+if ! typeset -f codex-use >/dev/null 2>&1; then
+  for source_file in \
+    /opt/zsh-kit/scripts/_features/agent-workspace/workspace-launcher.zsh \
     /opt/zsh-kit/scripts/_features/codex-workspace/workspace-launcher.zsh \
     /opt/zsh-kit/scripts/_features/agent-workspace/init.zsh \
     /opt/zsh-kit/scripts/_features/codex-workspace/init.zsh
@@ -1172,46 +3206,336 @@ codex-use "$profile"
                 println!("auth: codex -> {container} (synced auth file)");
                 0
             }
-            Err(err) => {
-                eprintln!("error: {err}");
-                EXIT_RUNTIME
+            Err(err) => {
+                eprintln!("error: {err}");
+                EXIT_RUNTIME
+            }
+        }
+    }
+}
+
+// Hashes a payload on the host via `sha256sum`, shelled out to the same way
+// as the other host tools this file runs (curl, gh, gpg, git).
+fn sha256_hex(payload: &[u8]) -> Result<String, String> {
+    let mut child = Command::new("sha256sum")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|err| format!("failed to run sha256sum: {err}"))?;
+    child
+        .stdin
+        .take()
+        .expect("piped stdin")
+        .write_all(payload)
+        .map_err(|err| format!("failed to hash payload: {err}"))?;
+    let output = child
+        .wait_with_output()
+        .map_err(|err| format!("failed to hash payload: {err}"))?;
+    if !output.status.success() {
+        return Err(String::from("sha256sum exited with an error"));
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .next()
+        .map(String::from)
+        .ok_or_else(|| String::from("unexpected sha256sum output"))
+}
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity((bytes.len() + 2) / 3 * 4);
+    for chunk in bytes.chunks(3) {
+        let b0 = chunk[0] as u32;
+        let b1 = chunk.get(1).copied().unwrap_or(0) as u32;
+        let b2 = chunk.get(2).copied().unwrap_or(0) as u32;
+        let n = (b0 << 16) | (b1 << 8) | b2;
+        out.push(BASE64_ALPHABET[((n >> 18) & 0x3f) as usize] as char);
+        out.push(BASE64_ALPHABET[((n >> 12) & 0x3f) as usize] as char);
+        out.push(if chunk.len() > 1 {
+            BASE64_ALPHABET[((n >> 6) & 0x3f) as usize] as char
+        } else {
+            '='
+        });
+        out.push(if chunk.len() > 2 {
+            BASE64_ALPHABET[(n & 0x3f) as usize] as char
+        } else {
+            '='
+        });
+    }
+    out
+}
+
+fn hex_to_bytes(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+// Subresource-integrity-style digest: `sha256-<base64 of the raw digest
+// bytes>`, matching the format used by SRI attributes and cacache.
+fn sri_digest(hex: &str) -> String {
+    let bytes = hex_to_bytes(hex).unwrap_or_default();
+    format!("sha256-{}", base64_encode(&bytes))
+}
+
+// Writes `payload` into a container at the path `target_snippet` resolves
+// (a bash fragment assigning `target=...`, so every step below agrees on
+// the same path), skipping the write entirely when the digest sidecar
+// already matches. After a real write the in-container file is re-hashed
+// and compared against the payload hash to catch a truncated or partial
+// `docker exec` stdin stream, then the sidecar is refreshed. Returns
+// `Ok(true)` when the write was skipped as unchanged.
+fn sync_file_with_integrity(container: &str, target_snippet: &str, payload: &[u8]) -> Result<bool, String> {
+    let payload_hash = sha256_hex(payload)?;
+    let expected_sri = sri_digest(&payload_hash);
+    let transport = resolve_transport();
+
+    let read_digest_script = format!(
+        "set -euo pipefail\n{target_snippet}\ncat \"$target.sha256\" 2>/dev/null\n"
+    );
+    let (code, stdout, _) = transport.exec_capture(
+        container,
+        Some(DEFAULT_CONTAINER_USER),
+        &["bash", "-c", &read_digest_script],
+    );
+    if code == 0 && String::from_utf8_lossy(&stdout).trim() == expected_sri {
+        return Ok(true);
+    }
+
+    let write_script = format!(
+        "set -euo pipefail\n{target_snippet}\nmkdir -p \"$(dirname \"$target\")\"\nrm -f -- \"$target\"\numask 077\ncat > \"$target\"\n"
+    );
+    match transport.exec_with_stdin(
+        container,
+        Some(DEFAULT_CONTAINER_USER),
+        &["bash", "-c", &write_script],
+        payload,
+    ) {
+        Ok(0) => {}
+        Ok(code) => return Err(format!("failed to write into {container} (exit {code})")),
+        Err(err) => return Err(err),
+    }
+
+    let rehash_script =
+        format!("set -euo pipefail\n{target_snippet}\nsha256sum \"$target\" 2>/dev/null | cut -d' ' -f1\n");
+    let (code, stdout, _) = transport.exec_capture(
+        container,
+        Some(DEFAULT_CONTAINER_USER),
+        &["bash", "-c", &rehash_script],
+    );
+    let actual_hash = String::from_utf8_lossy(&stdout).trim().to_string();
+    if code != 0 || actual_hash != payload_hash {
+        return Err(format!(
+            "integrity check failed after writing into {container}: hash mismatch (possible truncated write)"
+        ));
+    }
+
+    let store_digest_script = format!(
+        "set -euo pipefail\n{target_snippet}\numask 077\nprintf '%s' \"$1\" > \"$target.sha256\"\nchmod 600 \"$target.sha256\" 2>/dev/null || true\n"
+    );
+    if let Err(err) = transport.exec(
+        container,
+        Some(DEFAULT_CONTAINER_USER),
+        &["bash", "-c", &store_digest_script, "--", &expected_sri],
+    ) {
+        eprintln!("warn: failed to store integrity digest in {container}: {err}");
+    }
+
+    Ok(false)
+}
+
+const CODEX_AUTH_TARGET_SNIPPET: &str =
+    "target=\"${CODEX_AUTH_FILE:-$HOME/.codex/auth.json}\"\n[[ -n \"$target\" ]] || target=\"$HOME/.codex/auth.json\"";
+const CODEX_AUTH_ENC_TARGET_SNIPPET: &str =
+    "target=\"${CODEX_AUTH_FILE:-$HOME/.codex/auth.json}.enc\"\n[[ -n \"$target\" ]] || target=\"$HOME/.codex/auth.json.enc\"";
+
+// --- opt-in encrypted-at-rest secrets inside the container ----------------
+//
+// `AGENT_WORKSPACE_ENCRYPT=1` makes auth writers encrypt the payload on the
+// host before streaming it in, so a snapshotted or shared container
+// filesystem layer never holds the plaintext token/auth file. Reuses the
+// same bcrypt-pbkdf + AES-256-GCM primitives as the local credential store
+// (`seal_credential_bytes`); the container only ever sees salt || nonce ||
+// ciphertext and decrypts on demand via a staged python3 shim.
+
+const ENCRYPT_ENV: &str = "AGENT_WORKSPACE_ENCRYPT";
+
+fn encryption_enabled() -> bool {
+    matches!(
+        std::env::var(ENCRYPT_ENV).ok().as_deref(),
+        Some("1") | Some("true") | Some("yes")
+    )
+}
+
+fn encrypt_secret_payload(passphrase: &str, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    let (salt, nonce, ciphertext) = seal_credential_bytes(passphrase, plaintext)?;
+    let mut envelope = Vec::with_capacity(salt.len() + nonce.len() + ciphertext.len());
+    envelope.extend_from_slice(&salt);
+    envelope.extend_from_slice(&nonce);
+    envelope.extend_from_slice(&ciphertext);
+    Ok(envelope)
+}
+
+// In-container counterpart to `encrypt_secret_payload`: reads the salt ||
+// nonce || ciphertext envelope, prompts once for the passphrase, derives
+// the key with the `bcrypt` package's bcrypt_pbkdf-compatible `kdf`, and
+// decrypts straight to stdout so callers can redirect it into a tmpfs path
+// or pipe it into an fd without ever writing cleartext to the workspace
+// filesystem. Assumes python3 with the `bcrypt` and `cryptography`
+// packages available in the container.
+const DECRYPT_SHIM_PY: &str = r#"#!/usr/bin/env python3
+import sys
+import getpass
+import bcrypt
+from cryptography.hazmat.primitives.ciphers.aead import AESGCM
+
+
+def main():
+    if len(sys.argv) != 2:
+        print("usage: decrypt-secret.py <path>", file=sys.stderr)
+        sys.exit(2)
+    with open(sys.argv[1], "rb") as handle:
+        blob = handle.read()
+    salt, nonce, ciphertext = blob[:16], blob[16:28], blob[28:]
+    passphrase = getpass.getpass(f"passphrase for {sys.argv[1]}: ").encode()
+    key = bcrypt.kdf(password=passphrase, salt=salt, desired_key_bytes=32, rounds=16)
+    sys.stdout.buffer.write(AESGCM(key).decrypt(nonce, ciphertext, None))
+
+
+if __name__ == "__main__":
+    main()
+"#;
+
+const DECRYPT_SHIM_TARGET_SNIPPET: &str = "target=\"$HOME/.agents-env/decrypt-secret.py\"";
+
+fn stage_decrypt_shim(container: &str) -> Result<(), String> {
+    sync_file_with_integrity(container, DECRYPT_SHIM_TARGET_SNIPPET, DECRYPT_SHIM_PY.as_bytes())?;
+    resolve_transport()
+        .exec(
+            container,
+            Some(DEFAULT_CONTAINER_USER),
+            &[
+                "bash",
+                "-c",
+                "chmod 700 \"$HOME/.agents-env/decrypt-secret.py\" 2>/dev/null || true",
+            ],
+        )
+        .map(|_| ())
+}
+
+// Decrypts the staged codex auth envelope into `/dev/shm` (tmpfs) and
+// symlinks `~/.codex/auth.json` at it, so codex reads real JSON while the
+// plaintext never lands on the workspace filesystem. Re-run this after
+// every container restart, since tmpfs does not survive one.
+const CODEX_UNLOCK_SH: &str = r#"#!/usr/bin/env bash
+set -euo pipefail
+enc="${CODEX_AUTH_FILE:-$HOME/.codex/auth.json}.enc"
+tmp="/dev/shm/agent-workspace-codex-auth-$$.json"
+umask 077
+python3 "$HOME/.agents-env/decrypt-secret.py" "$enc" > "$tmp"
+mkdir -p "$HOME/.codex"
+ln -sf "$tmp" "$HOME/.codex/auth.json"
+echo "codex auth decrypted to tmpfs: $tmp"
+"#;
+
+const CODEX_UNLOCK_TARGET_SNIPPET: &str = "target=\"$HOME/.agents-env/codex-unlock.sh\"";
+
+fn stage_codex_unlock_helper(container: &str) -> Result<(), String> {
+    sync_file_with_integrity(container, CODEX_UNLOCK_TARGET_SNIPPET, CODEX_UNLOCK_SH.as_bytes())?;
+    resolve_transport()
+        .exec(
+            container,
+            Some(DEFAULT_CONTAINER_USER),
+            &[
+                "bash",
+                "-c",
+                "chmod 700 \"$HOME/.agents-env/codex-unlock.sh\" 2>/dev/null || true",
+            ],
+        )
+        .map(|_| ())
+}
+
+fn sync_codex_auth_into_container(container: &str, auth_data: &[u8]) -> Result<(), String> {
+    if encryption_enabled() {
+        let passphrase = resolve_store_passphrase()?;
+        let envelope = encrypt_secret_payload(&passphrase, auth_data)?;
+        stage_decrypt_shim(container)?;
+        stage_codex_unlock_helper(container)?;
+        return match sync_file_with_integrity(container, CODEX_AUTH_ENC_TARGET_SNIPPET, &envelope) {
+            Ok(true) => {
+                println!("auth: codex auth (encrypted) unchanged in {container}");
+                Ok(())
+            }
+            Ok(false) => {
+                println!(
+                    "auth: codex auth encrypted at rest in {container}; run `~/.agents-env/codex-unlock.sh` before using codex"
+                );
+                Ok(())
             }
+            Err(err) => Err(err),
+        };
+    }
+
+    match sync_file_with_integrity(container, CODEX_AUTH_TARGET_SNIPPET, auth_data) {
+        Ok(true) => {
+            println!("auth: codex auth file unchanged in {container}");
+            Ok(())
         }
+        Ok(false) => Ok(()),
+        Err(err) => Err(err),
     }
 }
 
-fn sync_codex_auth_into_container(container: &str, auth_data: &[u8]) -> Result<(), String> {
-    let script = r#"
-set -euo pipefail
-target="${CODEX_AUTH_FILE:-$HOME/.codex/auth.json}"
-[[ -n "$target" ]] || target="$HOME/.codex/auth.json"
-mkdir -p "$(dirname "$target")"
-rm -f -- "$target"
-umask 077
-cat > "$target"
-"#;
-
-    let mut cmd = Command::new("docker");
-    cmd.args([
-        "exec",
-        "-i",
-        "-u",
-        DEFAULT_CONTAINER_USER,
-        container,
-        "bash",
-        "-c",
-        script,
-    ]);
+// `--batch` forbids gpg from prompting, so exporting a passphrase-protected
+// secret key fails outright instead of hanging. Try the plain batch export
+// first (covers unprotected keys and keys gpg-agent already has cached),
+// then fall back to loopback pinentry fed from our askpass handler,
+// retrying once on a wrong passphrase.
+fn export_gpg_secret_key(key: &str) -> Result<Vec<u8>, String> {
+    let plain = Command::new("gpg")
+        .args(["--batch", "--armor", "--export-secret-keys", key])
+        .output()
+        .map_err(|err| format!("failed to export gpg key {key}: {err}"))?;
+    if plain.status.success() && !plain.stdout.is_empty() {
+        return Ok(plain.stdout);
+    }
 
-    match run_command_with_stdin(cmd, auth_data, "sync codex auth file") {
-        Ok(0) => Ok(()),
-        Ok(code) => Err(format!(
-            "failed to sync codex auth into {container} (exit {code})"
-        )),
-        Err(err) => Err(err),
+    let mut retried = false;
+    loop {
+        let mut passphrase = resolve_askpass(&format!("gpg passphrase for {key}: "))?;
+
+        let mut cmd = Command::new("gpg");
+        cmd.args([
+            "--batch",
+            "--pinentry-mode",
+            "loopback",
+            "--passphrase-fd",
+            "0",
+            "--armor",
+            "--export-secret-keys",
+            key,
+        ]);
+        let (stdout, success) = run_command_capturing_stdin(cmd, &passphrase)?;
+        zeroize_bytes(&mut passphrase);
+
+        if success && !stdout.is_empty() {
+            return Ok(stdout);
+        }
+        if retried {
+            return Err(format!("incorrect passphrase for gpg key {key}"));
+        }
+        retried = true;
     }
 }
 
+const GPG_IMPORT_TARGET_SNIPPET: &str = "target=\"$HOME/.gnupg/agent-kit-import.asc\"";
+
 fn run_auth_gpg(container: &str, key_arg: Option<&str>) -> i32 {
     let key = key_arg
         .and_then(trimmed_nonempty)
@@ -1238,28 +3562,27 @@ fn run_auth_gpg(container: &str, key_arg: Option<&str>) -> i32 {
 
     println!("auth: gpg -> {container} (key={key})");
 
-    let mut export_cmd = Command::new("gpg");
-    export_cmd.args(["--batch", "--armor", "--export-secret-keys", &key]);
-    export_cmd.stdout(Stdio::piped());
-
-    let mut export_child = match export_cmd.spawn() {
-        Ok(child) => child,
+    let exported = match export_gpg_secret_key(&key) {
+        Ok(bytes) => bytes,
         Err(err) => {
-            eprintln!("error: failed to export gpg key {key}: {err}");
+            eprintln!("error: {err}");
             return EXIT_RUNTIME;
         }
     };
 
-    let export_stdout = match export_child.stdout.take() {
-        Some(stdout) => stdout,
-        None => {
-            eprintln!("error: failed to capture gpg export stdout");
-            let _ = export_child.kill();
+    let transport = resolve_transport();
+    let staged_unchanged = match sync_file_with_integrity(container, GPG_IMPORT_TARGET_SNIPPET, &exported) {
+        Ok(skipped) => skipped,
+        Err(err) => {
+            eprintln!("error: {err}");
             return EXIT_RUNTIME;
         }
     };
 
-    let script = r#"
+    if staged_unchanged {
+        println!("auth: gpg key unchanged in {container} (skipping import)");
+    } else {
+        let import_script = r#"
 set -euo pipefail
 if ! command -v gpg >/dev/null 2>&1; then
   echo "error: gpg not installed in container" >&2
@@ -1268,211 +3591,713 @@ fi
 umask 077
 mkdir -p "$HOME/.gnupg"
 chmod 700 "$HOME/.gnupg" 2>/dev/null || true
-gpg --batch --import >/dev/null 2>&1
+gpg --batch --import "$HOME/.gnupg/agent-kit-import.asc" >/dev/null 2>&1
 "#;
+        match transport.exec(container, Some(DEFAULT_CONTAINER_USER), &["bash", "-c", import_script]) {
+            Ok(0) => {}
+            Ok(code) => {
+                eprintln!("error: failed to import gpg key into {container} (exit {code})");
+                return EXIT_RUNTIME;
+            }
+            Err(err) => {
+                eprintln!("error: {err}");
+                return EXIT_RUNTIME;
+            }
+        }
+    }
+
+    let verify_script = r#"gpg --list-secret-keys --keyid-format LONG -- "$1" >/dev/null 2>&1"#;
+    let verify_ok = transport
+        .exec(
+            container,
+            Some(DEFAULT_CONTAINER_USER),
+            &["bash", "-c", verify_script, "--", &key],
+        )
+        .map(|code| code == 0)
+        .unwrap_or(false);
+    if !verify_ok {
+        eprintln!("warn: gpg import completed but key lookup failed in container (key={key})");
+    }
+    0
+}
+
+fn ssh_dir() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| String::from("."));
+    PathBuf::from(home).join(".ssh")
+}
+
+// `ssh-add -L` only ever prints public key material, never the private
+// bytes, so "found in the agent" is cross-referenced against the
+// conventional identity files on disk by comparing the public key field —
+// the file backing a live agent identity is what actually gets copied in.
+fn ssh_agent_identity_paths() -> Vec<PathBuf> {
+    let Ok(output) = Command::new("ssh-add").arg("-L").output() else {
+        return Vec::new();
+    };
+    if !output.status.success() {
+        return Vec::new();
+    }
+    let listed = String::from_utf8_lossy(&output.stdout).into_owned();
+
+    [
+        ssh_dir().join("id_ed25519"),
+        ssh_dir().join("id_ecdsa"),
+        ssh_dir().join("id_rsa"),
+    ]
+    .into_iter()
+    .filter(|candidate| {
+        let pub_path = PathBuf::from(format!("{}.pub", candidate.display()));
+        let Ok(pub_contents) = fs::read_to_string(&pub_path) else {
+            return false;
+        };
+        let Some(pub_key_field) = pub_contents.split_whitespace().nth(1) else {
+            return false;
+        };
+        listed
+            .lines()
+            .any(|line| line.split_whitespace().nth(1) == Some(pub_key_field))
+    })
+    .collect()
+}
+
+// Mirrors the precedence real git tooling uses: agent-loaded keys first,
+// then an explicit `--key`/`AGENT_WORKSPACE_SSH_KEY` file, then the
+// conventional `~/.ssh/id_ed25519`/`id_rsa`.
+fn resolve_ssh_identity(key_arg: Option<&str>) -> Option<(PathBuf, &'static str)> {
+    if let Some(path) = ssh_agent_identity_paths().into_iter().next() {
+        return Some((path, "agent"));
+    }
+
+    if let Some(path) = key_arg.and_then(trimmed_nonempty) {
+        let path = PathBuf::from(path);
+        if path.is_file() {
+            return Some((path, "file"));
+        }
+        eprintln!("warn: --key path not found: {}", path.display());
+    }
+
+    if let Ok(value) = std::env::var("AGENT_WORKSPACE_SSH_KEY")
+        && let Some(cleaned) = trimmed_nonempty(&value)
+    {
+        let path = PathBuf::from(cleaned);
+        if path.is_file() {
+            return Some((path, "file"));
+        }
+    }
+
+    [ssh_dir().join("id_ed25519"), ssh_dir().join("id_rsa")]
+        .into_iter()
+        .find(|candidate| candidate.is_file())
+        .map(|path| (path, "file"))
+}
+
+fn copy_file_into_container(
+    container: &str,
+    local_path: &Path,
+    dest_name: &str,
+    mode: &str,
+) -> Result<(), String> {
+    let bytes = fs::read(local_path)
+        .map_err(|err| format!("failed to read {}: {err}", local_path.display()))?;
+    let script = format!(
+        r#"set -euo pipefail
+umask 077
+mkdir -p "$HOME/.ssh"
+chmod 700 "$HOME/.ssh"
+cat > "$HOME/.ssh/{dest_name}"
+chmod {mode} "$HOME/.ssh/{dest_name}"
+"#
+    );
+    let mut cmd = Command::new("docker");
+    cmd.args([
+        "exec",
+        "-i",
+        "-u",
+        DEFAULT_CONTAINER_USER,
+        container,
+        "bash",
+        "-c",
+        &script,
+    ]);
+    match run_command_with_stdin(cmd, &bytes, &format!("copy {dest_name} into {container}"))? {
+        0 => Ok(()),
+        code => Err(format!(
+            "failed to write {dest_name} into {container} (exit {code})"
+        )),
+    }
+}
 
-    let import_status = Command::new("docker")
+// Stages a one-shot SSH_ASKPASS helper inside the container so `ssh-add`
+// can unlock a passphrase-protected key non-interactively: the passphrase,
+// resolved via our own askpass handler, is written to a 0600 file that the
+// staged helper script cats back to ssh-add, then both are removed.
+fn unlock_ssh_key_in_container(container: &str, key_name: &str) -> Result<(), String> {
+    let mut passphrase = resolve_askpass(&format!("passphrase for ssh key {key_name}: "))?;
+
+    let stage_script = r#"
+set -euo pipefail
+umask 077
+mkdir -p "$HOME/.ssh-bin"
+cat > "$HOME/.ssh-bin/askpass.sh" <<'EOF'
+#!/bin/sh
+cat "$HOME/.ssh-bin/.askpass-secret"
+EOF
+chmod 700 "$HOME/.ssh-bin/askpass.sh"
+cat > "$HOME/.ssh-bin/.askpass-secret"
+chmod 600 "$HOME/.ssh-bin/.askpass-secret"
+"#;
+    let mut stage_cmd = Command::new("docker");
+    stage_cmd.args([
+        "exec",
+        "-i",
+        "-u",
+        DEFAULT_CONTAINER_USER,
+        container,
+        "bash",
+        "-c",
+        stage_script,
+    ]);
+    let stage_result = run_command_with_stdin(stage_cmd, &passphrase, "stage ssh askpass helper");
+    zeroize_bytes(&mut passphrase);
+    match stage_result? {
+        0 => {}
+        code => return Err(format!("failed to stage ssh askpass helper (exit {code})")),
+    }
+
+    let unlock_script = format!(
+        r#"set -euo pipefail
+eval "$(ssh-agent -s)" >/dev/null
+status=0
+SSH_ASKPASS="$HOME/.ssh-bin/askpass.sh" SSH_ASKPASS_REQUIRE=force DISPLAY=:0 setsid ssh-add "$HOME/.ssh/{key_name}" </dev/null || status=$?
+rm -f "$HOME/.ssh-bin/.askpass-secret" "$HOME/.ssh-bin/askpass.sh"
+exit $status
+"#
+    );
+    let status = Command::new("docker")
         .args([
             "exec",
-            "-i",
             "-u",
             DEFAULT_CONTAINER_USER,
             container,
             "bash",
             "-c",
-            script,
+            &unlock_script,
         ])
-        .stdin(Stdio::from(export_stdout))
-        .status();
+        .status()
+        .map_err(|err| format!("failed to run ssh-add in {container}: {err}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "ssh-add failed in {container} (exit {}) — wrong passphrase or setsid/DISPLAY unsupported",
+            status.code().unwrap_or(EXIT_RUNTIME)
+        ))
+    }
+}
 
-    let export_status = export_child.wait();
+fn run_auth_ssh(container: &str, key_arg: Option<&str>) -> i32 {
+    let Some((private_key_path, source)) = resolve_ssh_identity(key_arg) else {
+        eprintln!("error: no usable SSH key found");
+        eprintln!(
+            "hint: load a key in ssh-agent, pass --key <path>, or set AGENT_WORKSPACE_SSH_KEY"
+        );
+        return EXIT_RUNTIME;
+    };
 
-    match (export_status, import_status) {
-        (Ok(export), Ok(import)) if export.success() && import.success() => {
-            let verify_ok = Command::new("docker")
-                .args([
-                    "exec",
-                    "-u",
-                    DEFAULT_CONTAINER_USER,
-                    container,
-                    "gpg",
-                    "--list-secret-keys",
-                    "--keyid-format",
-                    "LONG",
-                    "--",
-                    &key,
-                ])
-                .stdout(Stdio::null())
-                .stderr(Stdio::null())
-                .status()
-                .map(|status| status.success())
-                .unwrap_or(false);
-            if !verify_ok {
-                eprintln!(
-                    "warn: gpg import completed but key lookup failed in container (key={key})"
-                );
+    if let Err(err) = ensure_container_running(container) {
+        eprintln!("error: {err}");
+        return EXIT_RUNTIME;
+    }
+
+    println!("auth: ssh -> {container} (source={source})");
+
+    let key_name = private_key_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("id_ed25519")
+        .to_string();
+
+    if let Err(err) = copy_file_into_container(container, &private_key_path, &key_name, "600") {
+        eprintln!("error: {err}");
+        return EXIT_RUNTIME;
+    }
+
+    let pub_path = PathBuf::from(format!("{}.pub", private_key_path.display()));
+    if pub_path.is_file() {
+        if let Err(err) =
+            copy_file_into_container(container, &pub_path, &format!("{key_name}.pub"), "644")
+        {
+            eprintln!("warn: {err}");
+        }
+    }
+
+    let known_hosts = ssh_dir().join("known_hosts");
+    if known_hosts.is_file() {
+        if let Err(err) = copy_file_into_container(container, &known_hosts, "known_hosts", "644")
+        {
+            eprintln!("warn: {err}");
+        }
+    }
+
+    let agent_script = format!(
+        r#"set -euo pipefail
+eval "$(ssh-agent -s)" >/dev/null
+ssh-add "$HOME/.ssh/{key_name}" </dev/null 2>/dev/null
+"#
+    );
+    let status = Command::new("docker")
+        .args([
+            "exec",
+            "-u",
+            DEFAULT_CONTAINER_USER,
+            container,
+            "bash",
+            "-c",
+            &agent_script,
+        ])
+        .status();
+    match status {
+        Ok(status) if status.success() => 0,
+        Ok(_) => {
+            // Unprotected-key add failed; most likely the key is
+            // passphrase-protected, so unlock it through the same askpass
+            // handler the gpg provider uses and retry once via an
+            // SSH_ASKPASS wrapper staged inside the container.
+            match unlock_ssh_key_in_container(container, &key_name) {
+                Ok(()) => 0,
+                Err(err) => {
+                    eprintln!("warn: {err}");
+                    0
+                }
             }
+        }
+        Err(err) => {
+            eprintln!("warn: failed to start ssh-agent in {container}: {err}");
             0
         }
-        (Ok(export), Ok(import)) => {
-            eprintln!(
-                "error: failed to import gpg key into {container} (export exit {}, import exit {})",
-                export.code().unwrap_or(EXIT_RUNTIME),
-                import.code().unwrap_or(EXIT_RUNTIME)
-            );
+    }
+}
+
+fn run_reset(args: &[OsString]) -> i32 {
+    if args.is_empty() {
+        print_reset_usage();
+        return 0;
+    }
+
+    let subcommand = args[0].to_string_lossy();
+    if matches!(subcommand.as_ref(), "-h" | "--help") {
+        print_reset_usage();
+        return 0;
+    }
+
+    match subcommand.as_ref() {
+        "repo" => run_reset_repo(&args[1..]),
+        "work-repos" => run_reset_work_repos(&args[1..]),
+        "opt-repos" => run_reset_opt_repos(&args[1..]),
+        "private-repo" => run_reset_private_repo(&args[1..]),
+        "--all" => run_reset_all(&args[1..]),
+        _ => {
+            eprintln!("error: unknown reset subcommand: {subcommand}");
+            eprintln!("hint: agent-workspace reset --help");
             EXIT_RUNTIME
         }
-        (Err(err), _) => {
-            eprintln!("error: failed while waiting for gpg export process: {err}");
-            EXIT_RUNTIME
+    }
+}
+
+fn run_reset_repo(args: &[OsString]) -> i32 {
+    let parsed = match parse_reset_repo_args(args) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("error: {err}");
+            print_reset_repo_usage();
+            return EXIT_RUNTIME;
         }
-        (_, Err(err)) => {
-            eprintln!("error: failed to run docker import for gpg auth: {err}");
-            EXIT_RUNTIME
+    };
+
+    if parsed.show_help {
+        print_reset_repo_usage();
+        return 0;
+    }
+
+    let repo_dir = if let Some(repo_dir) = parsed.repo_dir {
+        repo_dir
+    } else {
+        eprintln!("error: missing repo_dir");
+        print_reset_repo_usage();
+        return EXIT_RUNTIME;
+    };
+
+    let containers = match resolve_reset_targets(parsed.container.as_deref(), parsed.tag.as_deref()) {
+        Ok(containers) => containers,
+        Err(err) => {
+            eprintln!("error: {err}");
+            print_reset_repo_usage();
+            return EXIT_RUNTIME;
+        }
+    };
+
+    if !parsed.yes {
+        println!("This will reset a repo inside {} container(s):", containers.len());
+        for container in &containers {
+            println!("  - {container}: {repo_dir}");
+        }
+        if !confirm_or_abort("Proceed? [y/N] ") {
+            println!("Aborted");
+            return EXIT_RUNTIME;
+        }
+    }
+
+    let mut failed = 0usize;
+    for container in &containers {
+        if !docker_container_exists(container) {
+            eprintln!("error: workspace container not found: {container}");
+            failed += 1;
+            continue;
+        }
+        if let Err(err) = ensure_container_running(container) {
+            eprintln!("error: {err}");
+            failed += 1;
+            continue;
         }
+        if let Err(err) =
+            reset_repo_in_container(container, &repo_dir, &parsed.refspec, parsed.submodules)
+        {
+            eprintln!("error: {err}");
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        eprintln!("error: failed to reset repo in {failed} container(s)");
+        return EXIT_RUNTIME;
+    }
+    0
+}
+
+fn resolve_reset_targets(container: Option<&str>, tag: Option<&str>) -> Result<Vec<String>, String> {
+    if let Some(tag) = tag {
+        let targets = containers_for_tag(tag);
+        if targets.is_empty() {
+            return Err(format!("no workspace containers tagged: {tag}"));
+        }
+        return Ok(targets);
     }
+    if let Some(container) = container {
+        return Ok(vec![resolve_workspace_container_name_str(container)]);
+    }
+    if let Some(chosen) = pick_workspace_interactively() {
+        return Ok(vec![chosen]);
+    }
+    Err(String::from("missing container or --tag"))
 }
 
-fn run_reset(args: &[OsString]) -> i32 {
-    if args.is_empty() {
-        print_reset_usage();
+// Token-pool concurrency for `reset work-repos`: unlike `run_parallel`'s
+// fixed worker pool pulling from a shared queue, this spawns one thread per
+// repo and bounds how many run at once with a channel pre-filled with
+// `jobs` tokens — each thread blocks receiving a token before resetting its
+// repo and sends the token back when done. Since every reset is an
+// isolated `docker exec` subprocess there's no shared mutable state beyond
+// the token/result channels. Returns the number of repos that failed.
+fn reset_repos_with_job_pool(
+    container: &str,
+    repos: Vec<String>,
+    refspec: &str,
+    jobs: usize,
+    submodules: bool,
+) -> usize {
+    use std::sync::mpsc;
+
+    if repos.is_empty() {
         return 0;
     }
+    let jobs = jobs.max(1);
 
-    let subcommand = args[0].to_string_lossy();
-    if matches!(subcommand.as_ref(), "-h" | "--help") {
-        print_reset_usage();
-        return 0;
+    let (token_tx, token_rx) = mpsc::channel::<()>();
+    for _ in 0..jobs {
+        let _ = token_tx.send(());
     }
+    let token_rx = Mutex::new(token_rx);
 
-    match subcommand.as_ref() {
-        "repo" => run_reset_repo(&args[1..]),
-        "work-repos" => run_reset_work_repos(&args[1..]),
-        "opt-repos" => run_reset_opt_repos(&args[1..]),
-        "private-repo" => run_reset_private_repo(&args[1..]),
-        _ => {
-            eprintln!("error: unknown reset subcommand: {subcommand}");
-            eprintln!("hint: agent-workspace reset --help");
-            EXIT_RUNTIME
+    let (result_tx, result_rx) = mpsc::channel::<(String, i32, Vec<u8>, Vec<u8>)>();
+
+    std::thread::scope(|scope| {
+        for repo in &repos {
+            let token_tx = token_tx.clone();
+            let result_tx = result_tx.clone();
+            let token_rx = &token_rx;
+            scope.spawn(move || {
+                {
+                    let rx = token_rx.lock().expect("token channel poisoned");
+                    let _ = rx.recv();
+                }
+                let (code, stdout, stderr) =
+                    reset_repo_in_container_capture(container, repo, refspec, submodules);
+                let _ = result_tx.send((repo.clone(), code, stdout, stderr));
+                let _ = token_tx.send(());
+            });
+        }
+    });
+    drop(result_tx);
+
+    // Each worker runs its reset concurrently but only captures output; it is
+    // flushed here, one repo at a time, so `--jobs > 1` never interleaves two
+    // repos' `RESET_REPO_SCRIPT` output the way streaming directly to the
+    // parent's stdout/stderr would.
+    let mut failed = 0usize;
+    for (repo, code, stdout, stderr) in result_rx {
+        if !stdout.is_empty() {
+            let _ = std::io::stdout().write_all(&stdout);
+        }
+        if !stderr.is_empty() {
+            let _ = std::io::stderr().write_all(&stderr);
+        }
+        if code == 0 {
+            println!("reset ok: {container}: {repo}");
+        } else {
+            eprintln!("error: {container}: {repo}: failed to reset repo (exit {code})");
+            failed += 1;
         }
     }
+    failed
 }
 
-fn run_reset_repo(args: &[OsString]) -> i32 {
-    let parsed = match parse_reset_repo_args(args) {
+fn run_reset_work_repos(args: &[OsString]) -> i32 {
+    let parsed = match parse_reset_work_repos_args(args) {
         Ok(parsed) => parsed,
         Err(err) => {
             eprintln!("error: {err}");
-            print_reset_repo_usage();
+            print_reset_work_repos_usage();
             return EXIT_RUNTIME;
         }
     };
 
     if parsed.show_help {
-        print_reset_repo_usage();
+        print_reset_work_repos_usage();
         return 0;
     }
 
-    let container_name = if let Some(container) = parsed.container {
-        container
-    } else {
-        eprintln!("error: missing container");
-        print_reset_repo_usage();
-        return EXIT_RUNTIME;
-    };
-    let repo_dir = if let Some(repo_dir) = parsed.repo_dir {
-        repo_dir
-    } else {
-        eprintln!("error: missing repo_dir");
-        print_reset_repo_usage();
-        return EXIT_RUNTIME;
+    let containers = match resolve_reset_targets(parsed.container.as_deref(), parsed.tag.as_deref()) {
+        Ok(containers) => containers,
+        Err(err) => {
+            eprintln!("error: {err}");
+            print_reset_work_repos_usage();
+            return EXIT_RUNTIME;
+        }
     };
 
-    let container = resolve_workspace_container_name_str(&container_name);
-    if !docker_container_exists(&container) {
-        eprintln!("error: workspace container not found: {container}");
-        return EXIT_RUNTIME;
+    let mut failed = 0usize;
+    for container in &containers {
+        if !docker_container_exists(container) {
+            eprintln!("error: workspace container not found: {container}");
+            failed += 1;
+            continue;
+        }
+        if let Err(err) = ensure_container_running(container) {
+            eprintln!("error: {err}");
+            failed += 1;
+            continue;
+        }
+
+        let repos = match list_git_repos_in_container(container, &parsed.root, parsed.depth) {
+            Ok(repos) => repos,
+            Err(err) => {
+                eprintln!("error: {err}");
+                failed += 1;
+                continue;
+            }
+        };
+
+        if repos.is_empty() {
+            eprintln!(
+                "warn: no git repos found under {} (depth={}) in {}",
+                parsed.root, parsed.depth, container
+            );
+            continue;
+        }
+
+        if !parsed.yes {
+            println!(
+                "This will reset {} repos inside container: {}",
+                repos.len(),
+                container
+            );
+            for repo in &repos {
+                println!("  - {repo}");
+            }
+            if !confirm_or_abort("Proceed? [y/N] ") {
+                println!("Aborted");
+                return EXIT_RUNTIME;
+            }
+        }
+
+        failed += reset_repos_with_job_pool(
+            container,
+            repos,
+            &parsed.refspec,
+            parsed.jobs,
+            parsed.submodules,
+        );
     }
-    if let Err(err) = ensure_container_running(&container) {
-        eprintln!("error: {err}");
+    if failed > 0 {
+        eprintln!("error: failed to reset {failed} repo(s)");
         return EXIT_RUNTIME;
     }
+    0
+}
 
-    if !parsed.yes {
-        println!("This will reset a repo inside container: {container}");
-        println!("  - {repo_dir}");
-        if !confirm_or_abort("Proceed? [y/N] ") {
-            println!("Aborted");
-            return EXIT_RUNTIME;
+#[derive(Debug, Clone)]
+struct ParsedResetAll {
+    show_help: bool,
+    root: String,
+    depth: u32,
+    refspec: String,
+    jobs: Option<usize>,
+    yes: bool,
+}
+
+impl Default for ParsedResetAll {
+    fn default() -> Self {
+        Self {
+            show_help: false,
+            root: String::from("/work"),
+            depth: 3,
+            refspec: String::from(DEFAULT_REF),
+            jobs: None,
+            yes: false,
         }
     }
+}
 
-    match reset_repo_in_container(&container, &repo_dir, &parsed.refspec) {
-        Ok(()) => 0,
-        Err(err) => {
-            eprintln!("error: {err}");
-            EXIT_RUNTIME
+fn parse_reset_all_args(args: &[OsString]) -> Result<ParsedResetAll, String> {
+    let mut parsed = ParsedResetAll::default();
+    let mut idx = 0usize;
+    while idx < args.len() {
+        let text = args[idx].to_string_lossy();
+        match text.as_ref() {
+            "-h" | "--help" => parsed.show_help = true,
+            "--root" => {
+                idx += 1;
+                if idx >= args.len() {
+                    return Err(String::from("missing value for --root"));
+                }
+                parsed.root = args[idx].to_string_lossy().into_owned();
+            }
+            "--depth" => {
+                idx += 1;
+                if idx >= args.len() {
+                    return Err(String::from("missing value for --depth"));
+                }
+                parsed.depth = args[idx]
+                    .to_string_lossy()
+                    .parse::<u32>()
+                    .map_err(|_| String::from("--depth must be a positive integer"))?;
+            }
+            "--ref" => {
+                idx += 1;
+                if idx >= args.len() {
+                    return Err(String::from("missing value for --ref"));
+                }
+                parsed.refspec = args[idx].to_string_lossy().into_owned();
+            }
+            "--jobs" => {
+                idx += 1;
+                if idx >= args.len() {
+                    return Err(String::from("missing value for --jobs"));
+                }
+                parsed.jobs = Some(parse_jobs_value(&args[idx].to_string_lossy())?);
+            }
+            "-y" | "--yes" => parsed.yes = true,
+            _ if text.starts_with("--root=") => parsed.root = text["--root=".len()..].to_string(),
+            _ if text.starts_with("--depth=") => {
+                parsed.depth = text["--depth=".len()..]
+                    .parse::<u32>()
+                    .map_err(|_| String::from("--depth must be a positive integer"))?;
+            }
+            _ if text.starts_with("--ref=") => parsed.refspec = text["--ref=".len()..].to_string(),
+            _ if text.starts_with("--jobs=") => {
+                parsed.jobs = Some(parse_jobs_value(&text["--jobs=".len()..])?);
+            }
+            _ if text.starts_with('-') => return Err(format!("unknown arg: {text}")),
+            _ => return Err(format!("unexpected arg: {text}")),
         }
+        idx += 1;
     }
+    if parsed.depth == 0 {
+        return Err(String::from("--depth must be a positive integer"));
+    }
+    Ok(parsed)
 }
 
-fn run_reset_work_repos(args: &[OsString]) -> i32 {
-    let parsed = match parse_reset_work_repos_args(args) {
+fn print_reset_all_usage() {
+    eprintln!(
+        "usage: agent-workspace reset --all [--root <dir>] [--depth <N>] [--ref <remote/branch>] [--jobs N] [--yes]"
+    );
+}
+
+fn run_reset_all(args: &[OsString]) -> i32 {
+    let parsed = match parse_reset_all_args(args) {
         Ok(parsed) => parsed,
         Err(err) => {
             eprintln!("error: {err}");
-            print_reset_work_repos_usage();
+            print_reset_all_usage();
             return EXIT_RUNTIME;
         }
     };
 
     if parsed.show_help {
-        print_reset_work_repos_usage();
+        print_reset_all_usage();
         return 0;
     }
 
-    let container_name = if let Some(container) = parsed.container {
-        container
-    } else {
-        eprintln!("error: missing container");
-        print_reset_work_repos_usage();
-        return EXIT_RUNTIME;
-    };
-
-    let container = resolve_workspace_container_name_str(&container_name);
-    if !docker_container_exists(&container) {
-        eprintln!("error: workspace container not found: {container}");
-        return EXIT_RUNTIME;
-    }
-    if let Err(err) = ensure_container_running(&container) {
-        eprintln!("error: {err}");
-        return EXIT_RUNTIME;
-    }
-
-    let repos = match list_git_repos_in_container(&container, &parsed.root, parsed.depth) {
-        Ok(repos) => repos,
+    let containers = match list_workspaces() {
+        Ok(items) => items,
         Err(err) => {
             eprintln!("error: {err}");
             return EXIT_RUNTIME;
         }
     };
+    if containers.is_empty() {
+        eprintln!("warn: no workspace containers found");
+        return 0;
+    }
 
-    if repos.is_empty() {
+    let mut targets: Vec<String> = Vec::new();
+    for container in &containers {
+        if ensure_container_running(container).is_err() {
+            eprintln!("warn: skipping unreachable container: {container}");
+            continue;
+        }
+        let repos = match list_git_repos_in_container(container, &parsed.root, parsed.depth) {
+            Ok(repos) => repos,
+            Err(err) => {
+                eprintln!("warn: {err}");
+                continue;
+            }
+        };
+        for repo in repos {
+            targets.push(format!("{container}\t{repo}"));
+        }
+    }
+
+    if targets.is_empty() {
         eprintln!(
-            "warn: no git repos found under {} (depth={}) in {}",
-            parsed.root, parsed.depth, container
+            "warn: no git repos found under {} (depth={}) across {} workspace(s)",
+            parsed.root,
+            parsed.depth,
+            containers.len()
         );
         return 0;
     }
 
     if !parsed.yes {
         println!(
-            "This will reset {} repos inside container: {}",
-            repos.len(),
-            container
+            "This will reset {} repo(s) across {} workspace(s):",
+            targets.len(),
+            containers.len()
         );
-        for repo in &repos {
-            println!("  - {repo}");
+        for target in &targets {
+            if let Some((container, repo)) = target.split_once('\t') {
+                println!("  - {container}: {repo}");
+            }
         }
         if !confirm_or_abort("Proceed? [y/N] ") {
             println!("Aborted");
@@ -1480,17 +4305,20 @@ fn run_reset_work_repos(args: &[OsString]) -> i32 {
         }
     }
 
-    let mut failed = 0usize;
-    for repo in repos {
-        if reset_repo_in_container(&container, &repo, &parsed.refspec).is_err() {
-            failed += 1;
+    let jobs = parsed.jobs.unwrap_or_else(default_parallelism);
+    let refspec = parsed.refspec.clone();
+    let outcomes = run_parallel(jobs, targets, |target| {
+        let (container, repo_dir) = target.split_once('\t').expect("target has container/repo");
+        let (exit_code, stdout, stderr) =
+            reset_repo_in_container_capture(container, repo_dir, &refspec, false);
+        JobOutcome {
+            target: target.to_string(),
+            exit_code,
+            stdout,
+            stderr,
         }
-    }
-    if failed > 0 {
-        eprintln!("error: failed to reset {failed} repo(s)");
-        return EXIT_RUNTIME;
-    }
-    0
+    });
+    summarize_job_outcomes(&outcomes)
 }
 
 fn run_reset_opt_repos(args: &[OsString]) -> i32 {
@@ -1545,7 +4373,9 @@ fn run_reset_opt_repos(args: &[OsString]) -> i32 {
                 return EXIT_RUNTIME;
             }
         };
-        if has_repo && let Err(err) = reset_repo_in_container(&container, repo_dir, DEFAULT_REF) {
+        if has_repo
+            && let Err(err) = reset_repo_in_container(&container, repo_dir, DEFAULT_REF, false)
+        {
             eprintln!("error: {err}");
             return EXIT_RUNTIME;
         }
@@ -1630,7 +4460,12 @@ fn run_reset_private_repo(args: &[OsString]) -> i32 {
         }
     }
 
-    match reset_repo_in_container(&container, &private_repo_dir, &parsed.refspec) {
+    match reset_repo_in_container(
+        &container,
+        &private_repo_dir,
+        &parsed.refspec,
+        parsed.submodules,
+    ) {
         Ok(()) => 0,
         Err(err) => {
             eprintln!("error: {err}");
@@ -1643,9 +4478,11 @@ fn run_reset_private_repo(args: &[OsString]) -> i32 {
 struct ParsedResetRepo {
     show_help: bool,
     container: Option<String>,
+    tag: Option<String>,
     repo_dir: Option<String>,
     refspec: String,
     yes: bool,
+    submodules: bool,
 }
 
 impl Default for ParsedResetRepo {
@@ -1653,9 +4490,11 @@ impl Default for ParsedResetRepo {
         Self {
             show_help: false,
             container: None,
+            tag: None,
             repo_dir: None,
             refspec: String::from(DEFAULT_REF),
             yes: false,
+            submodules: false,
         }
     }
 }
@@ -1674,10 +4513,21 @@ fn parse_reset_repo_args(args: &[OsString]) -> Result<ParsedResetRepo, String> {
                 }
                 parsed.refspec = args[idx].to_string_lossy().into_owned();
             }
+            "--tag" => {
+                idx += 1;
+                if idx >= args.len() {
+                    return Err(String::from("missing value for --tag"));
+                }
+                parsed.tag = Some(args[idx].to_string_lossy().into_owned());
+            }
             "-y" | "--yes" => parsed.yes = true,
+            "--submodules" => parsed.submodules = true,
             _ if text.starts_with("--ref=") => {
                 parsed.refspec = text["--ref=".len()..].to_string();
             }
+            _ if text.starts_with("--tag=") => {
+                parsed.tag = Some(text["--tag=".len()..].to_string());
+            }
             _ if text.starts_with('-') => return Err(format!("unknown arg: {text}")),
             _ => {
                 if parsed.container.is_none() {
@@ -1698,10 +4548,13 @@ fn parse_reset_repo_args(args: &[OsString]) -> Result<ParsedResetRepo, String> {
 struct ParsedResetWorkRepos {
     show_help: bool,
     container: Option<String>,
+    tag: Option<String>,
     root: String,
     depth: u32,
     refspec: String,
     yes: bool,
+    jobs: usize,
+    submodules: bool,
 }
 
 impl Default for ParsedResetWorkRepos {
@@ -1709,10 +4562,13 @@ impl Default for ParsedResetWorkRepos {
         Self {
             show_help: false,
             container: None,
+            tag: None,
             root: String::from("/work"),
             depth: 3,
             refspec: String::from(DEFAULT_REF),
             yes: false,
+            jobs: 1,
+            submodules: false,
         }
     }
 }
@@ -1724,6 +4580,13 @@ fn parse_reset_work_repos_args(args: &[OsString]) -> Result<ParsedResetWorkRepos
         let text = args[idx].to_string_lossy();
         match text.as_ref() {
             "-h" | "--help" => parsed.show_help = true,
+            "--tag" => {
+                idx += 1;
+                if idx >= args.len() {
+                    return Err(String::from("missing value for --tag"));
+                }
+                parsed.tag = Some(args[idx].to_string_lossy().into_owned());
+            }
             "--root" => {
                 idx += 1;
                 if idx >= args.len() {
@@ -1749,6 +4612,14 @@ fn parse_reset_work_repos_args(args: &[OsString]) -> Result<ParsedResetWorkRepos
                 parsed.refspec = args[idx].to_string_lossy().into_owned();
             }
             "-y" | "--yes" => parsed.yes = true,
+            "--jobs" => {
+                idx += 1;
+                if idx >= args.len() {
+                    return Err(String::from("missing value for --jobs"));
+                }
+                parsed.jobs = parse_jobs_value(&args[idx].to_string_lossy())?;
+            }
+            "--submodules" => parsed.submodules = true,
             _ if text.starts_with("--root=") => parsed.root = text["--root=".len()..].to_string(),
             _ if text.starts_with("--depth=") => {
                 parsed.depth = text["--depth=".len()..]
@@ -1756,6 +4627,12 @@ fn parse_reset_work_repos_args(args: &[OsString]) -> Result<ParsedResetWorkRepos
                     .map_err(|_| String::from("--depth must be a positive integer"))?;
             }
             _ if text.starts_with("--ref=") => parsed.refspec = text["--ref=".len()..].to_string(),
+            _ if text.starts_with("--tag=") => {
+                parsed.tag = Some(text["--tag=".len()..].to_string());
+            }
+            _ if text.starts_with("--jobs=") => {
+                parsed.jobs = parse_jobs_value(&text["--jobs=".len()..])?;
+            }
             _ if text.starts_with('-') => return Err(format!("unknown arg: {text}")),
             _ => {
                 if parsed.container.is_none() {
@@ -1806,6 +4683,7 @@ struct ParsedResetPrivate {
     container: Option<String>,
     refspec: String,
     yes: bool,
+    submodules: bool,
 }
 
 impl Default for ParsedResetPrivate {
@@ -1815,6 +4693,7 @@ impl Default for ParsedResetPrivate {
             container: None,
             refspec: String::from(DEFAULT_REF),
             yes: false,
+            submodules: false,
         }
     }
 }
@@ -1834,6 +4713,7 @@ fn parse_reset_private_repo_args(args: &[OsString]) -> Result<ParsedResetPrivate
                 parsed.refspec = args[idx].to_string_lossy().into_owned();
             }
             "-y" | "--yes" => parsed.yes = true,
+            "--submodules" => parsed.submodules = true,
             _ if text.starts_with("--ref=") => parsed.refspec = text["--ref=".len()..].to_string(),
             _ if text.starts_with('-') => return Err(format!("unknown arg: {text}")),
             _ => {
@@ -1850,45 +4730,58 @@ fn parse_reset_private_repo_args(args: &[OsString]) -> Result<ParsedResetPrivate
 }
 
 fn print_exec_usage() {
-    eprintln!("usage: agent-workspace exec [--root|--user <user>] <workspace> [command ...]");
+    eprintln!(
+        "usage: agent-workspace exec [--root|--user <user>] <workspace>|--tag <tag> [command ...]"
+    );
 }
 
 fn print_rm_usage() {
-    eprintln!("usage: agent-workspace rm [--all] [--yes] <workspace>");
+    eprintln!("usage: agent-workspace rm [--all] [--tag <tag>] [--jobs N] [--yes] <workspace>");
 }
 
 fn print_auth_usage() {
     eprintln!("usage:");
     eprintln!("  agent-workspace auth codex [--profile <name>] [--container <name|container>]");
-    eprintln!("  agent-workspace auth github [--host <host>] [--container <name|container>]");
+    eprintln!(
+        "  agent-workspace auth github [--host <host>] [--container <name|container>] [--save-credential]"
+    );
     eprintln!(
         "  agent-workspace auth gpg [--key <keyid|fingerprint>] [--container <name|container>]"
     );
+    eprintln!(
+        "  agent-workspace auth ssh [--key <path>] [--container <name|container>]"
+    );
+    eprintln!("  agent-workspace auth <provider> --all [--yes]");
+    eprintln!("  agent-workspace auth --list");
+    eprintln!("  agent-workspace auth <provider> --remove [--host <host>] [--profile <name>]");
 }
 
 fn print_reset_usage() {
     eprintln!("usage:");
     eprintln!(
-        "  agent-workspace reset repo <name|container> <repo_dir> [--ref <remote/branch>] [--yes]"
+        "  agent-workspace reset repo <name|container>|--tag <tag> <repo_dir> [--ref <remote/branch>] [--yes]"
     );
     eprintln!(
-        "  agent-workspace reset work-repos <name|container> [--root <dir>] [--depth <N>] [--ref <remote/branch>] [--yes]"
+        "  agent-workspace reset work-repos <name|container>|--tag <tag> [--root <dir>] [--depth <N>] [--ref <remote/branch>] [--yes]"
     );
     eprintln!("  agent-workspace reset opt-repos <name|container> [--yes]");
     eprintln!(
         "  agent-workspace reset private-repo <name|container> [--ref <remote/branch>] [--yes]"
     );
+    eprintln!(
+        "  agent-workspace reset --all [--root <dir>] [--depth <N>] [--ref <remote/branch>] [--jobs N] [--yes]"
+    );
 }
 
 fn print_reset_repo_usage() {
     eprintln!(
-        "usage: agent-workspace reset repo <name|container> <repo_dir> [--ref <remote/branch>] [--yes]"
+        "usage: agent-workspace reset repo <name|container>|--tag <tag> <repo_dir> [--ref <remote/branch>] [--submodules] [--yes]"
     );
 }
 
 fn print_reset_work_repos_usage() {
     eprintln!(
-        "usage: agent-workspace reset work-repos <name|container> [--root <dir>] [--depth <N>] [--ref <remote/branch>] [--yes]"
+        "usage: agent-workspace reset work-repos <name|container>|--tag <tag> [--root <dir>] [--depth <N>] [--ref <remote/branch>] [--jobs <N>] [--submodules] [--yes]"
     );
 }
 
@@ -1898,7 +4791,7 @@ fn print_reset_opt_repos_usage() {
 
 fn print_reset_private_repo_usage() {
     eprintln!(
-        "usage: agent-workspace reset private-repo <name|container> [--ref <remote/branch>] [--yes]"
+        "usage: agent-workspace reset private-repo <name|container> [--ref <remote/branch>] [--submodules] [--yes]"
     );
 }
 
@@ -1994,49 +4887,28 @@ fn normalize_workspace_name_for_create(name: &str) -> String {
 }
 
 fn docker_container_exists(name: &str) -> bool {
-    Command::new("docker")
-        .args(["container", "inspect", name])
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .status()
-        .map(|status| status.success())
-        .unwrap_or(false)
+    resolve_runtime().container_exists(name)
 }
 
 fn ensure_container_running(container: &str) -> Result<(), String> {
-    if !docker_container_exists(container) {
-        return Err(format!("workspace container not found: {container}"));
+    if is_kubectl_transport() {
+        return match resolve_transport().running(container) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(format!("pod for workspace {container} is not running")),
+            Err(err) => Err(err),
+        };
     }
 
-    let running = Command::new("docker")
-        .args(["inspect", "-f", "{{.State.Running}}", container])
-        .output()
-        .map_err(|err| format!("failed to inspect workspace {container}: {err}"))?;
-    if !running.status.success() {
-        let stderr = String::from_utf8_lossy(&running.stderr).trim().to_string();
-        return Err(format!(
-            "docker inspect failed for {container} (exit {}): {stderr}",
-            running.status.code().unwrap_or(EXIT_RUNTIME)
-        ));
+    let runtime = resolve_runtime();
+    if !runtime.container_exists(container) {
+        return Err(format!("workspace container not found: {container}"));
     }
 
-    let is_running = String::from_utf8_lossy(&running.stdout).trim().eq("true");
-    if is_running {
+    if runtime.inspect_running(container)? {
         return Ok(());
     }
 
-    let status = Command::new("docker")
-        .args(["start", container])
-        .status()
-        .map_err(|err| format!("failed to start workspace {container}: {err}"))?;
-    if status.success() {
-        Ok(())
-    } else {
-        Err(format!(
-            "docker start failed for {container} (exit {})",
-            status.code().unwrap_or(EXIT_RUNTIME)
-        ))
-    }
+    runtime.start(container)
 }
 
 fn resolve_container_for_auth(name: Option<&str>) -> Result<String, String> {
@@ -2052,40 +4924,18 @@ fn resolve_container_for_auth(name: Option<&str>) -> Result<String, String> {
     match workspaces.as_slice() {
         [] => Err(String::from("no workspaces found")),
         [single] => Ok(single.clone()),
-        _ => Err(format!(
-            "multiple workspaces found; specify one: {}",
-            workspaces.join(", ")
-        )),
+        _ => match interactive_stdio().then(|| pick_interactively(&workspaces)).flatten() {
+            Some(chosen) => Ok(chosen),
+            None => Err(format!(
+                "multiple workspaces found; specify one: {}",
+                workspaces.join(", ")
+            )),
+        },
     }
 }
 
 fn list_workspaces() -> Result<Vec<String>, String> {
-    let output = Command::new("docker")
-        .args([
-            "ps",
-            "-a",
-            "--filter",
-            "label=agent-kit.workspace=1",
-            "--format",
-            "{{.Names}}",
-        ])
-        .output()
-        .map_err(|err| format!("failed to list workspaces via docker: {err}"))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
-        return Err(format!(
-            "docker ps failed (exit {}): {stderr}",
-            output.status.code().unwrap_or(EXIT_RUNTIME)
-        ));
-    }
-
-    Ok(String::from_utf8_lossy(&output.stdout)
-        .lines()
-        .map(str::trim)
-        .filter(|line| !line.is_empty())
-        .map(str::to_owned)
-        .collect())
+    resolve_runtime().list_by_label("agent-kit.workspace=1", true)
 }
 
 fn run_command_with_stdin(mut cmd: Command, input: &[u8], context: &str) -> Result<i32, String> {
@@ -2104,61 +4954,186 @@ fn run_command_with_stdin(mut cmd: Command, input: &[u8], context: &str) -> Resu
     Ok(status.code().unwrap_or(EXIT_RUNTIME))
 }
 
-fn reset_repo_in_container(container: &str, repo_dir: &str, refspec: &str) -> Result<(), String> {
-    let status = Command::new("docker")
-        .args([
-            "exec",
-            "-i",
-            "-u",
-            DEFAULT_CONTAINER_USER,
-            container,
+// Same shape as `run_command_with_stdin`, but also captures stdout —
+// used by the gpg loopback-pinentry export, which needs the exported key
+// bytes back on success.
+fn run_command_capturing_stdin(mut cmd: Command, input: &[u8]) -> Result<(Vec<u8>, bool), String> {
+    cmd.stdin(Stdio::piped());
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::null());
+    let mut child = cmd.spawn().map_err(|err| format!("failed to spawn: {err}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input)
+            .map_err(|err| format!("failed to write stdin: {err}"))?;
+        stdin
+            .write_all(b"\n")
+            .map_err(|err| format!("failed to write stdin: {err}"))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|err| format!("failed to wait for process: {err}"))?;
+    Ok((output.stdout, output.status.success()))
+}
+
+const ASKPASS_ENV: &str = "AGENT_WORKSPACE_ASKPASS";
+
+// Best-effort zeroing so a passphrase doesn't linger in memory longer than
+// necessary. Uses a volatile write per byte, the same idiom the `zeroize`
+// crate relies on, so the compiler can't optimize the clear away.
+fn zeroize_bytes(buffer: &mut [u8]) {
+    for byte in buffer.iter_mut() {
+        unsafe { std::ptr::write_volatile(byte, 0) };
+    }
+    std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+}
+
+// Mirrors git's GIT_ASKPASS/SSH_ASKPASS convention: prompt on the
+// controlling TTY when one is attached, otherwise shell out to an
+// AGENT_WORKSPACE_ASKPASS helper program (invoked with the prompt text as
+// its sole argument, expected to print the secret on stdout). Shared by
+// the gpg and ssh providers for unlocking passphrase-protected keys.
+fn resolve_askpass(prompt: &str) -> Result<Vec<u8>, String> {
+    if std::io::stdin().is_terminal() {
+        eprint!("{prompt}");
+        let _ = std::io::stderr().flush();
+        let mut input = String::new();
+        std::io::stdin()
+            .read_line(&mut input)
+            .map_err(|err| format!("failed to read passphrase: {err}"))?;
+        let trimmed = input.trim_end_matches(['\n', '\r']);
+        return Ok(trimmed.as_bytes().to_vec());
+    }
+
+    let helper = std::env::var(ASKPASS_ENV)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .ok_or_else(|| format!("not a TTY and no askpass helper configured (set {ASKPASS_ENV})"))?;
+
+    let output = Command::new(&helper)
+        .arg(prompt)
+        .output()
+        .map_err(|err| format!("failed to run askpass helper {helper}: {err}"))?;
+    if !output.status.success() {
+        return Err(format!("askpass helper {helper} exited with failure"));
+    }
+    let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+    while text.ends_with(['\n', '\r']) {
+        text.pop();
+    }
+    Ok(text.into_bytes())
+}
+
+// Reads the `origin` remote URL for `repo_dir` inside `container`, so the
+// forge lookup below can confirm the repo and learn its real default
+// branch without the caller having to already know its host/owner/repo.
+fn read_container_repo_origin(container: &str, repo_dir: &str) -> Option<String> {
+    let transport = resolve_transport();
+    let (code, stdout, _stderr) = transport.exec_capture(
+        container,
+        Some(DEFAULT_CONTAINER_USER),
+        &["git", "-C", repo_dir, "remote", "get-url", "origin"],
+    );
+    if code != 0 {
+        return None;
+    }
+    trimmed_nonempty(String::from_utf8_lossy(&stdout).as_ref())
+}
+
+// When the ref is still `DEFAULT_REF` (no `--ref` given, or an explicit
+// `--ref` equal to it, which cannot be told apart), try to learn the repo's
+// real default branch from the forge API instead of blindly assuming
+// `DEFAULT_REF`; on any failure this returns `refspec` unchanged and
+// `RESET_REPO_SCRIPT`'s existing shell heuristics take over.
+fn resolve_reset_ref(container: &str, repo_dir: &str, refspec: &str) -> String {
+    if refspec != DEFAULT_REF {
+        return refspec.to_string();
+    }
+    let Some(origin_url) = read_container_repo_origin(container, repo_dir) else {
+        return refspec.to_string();
+    };
+    let Some(spec) = parse_repo_spec(&origin_url, "github.com") else {
+        return refspec.to_string();
+    };
+    match resolve_forge_default_branch(&spec) {
+        Some(branch) => format!("origin/{branch}"),
+        None => refspec.to_string(),
+    }
+}
+
+fn reset_repo_in_container(
+    container: &str,
+    repo_dir: &str,
+    refspec: &str,
+    submodules: bool,
+) -> Result<(), String> {
+    let refspec = resolve_reset_ref(container, repo_dir, refspec);
+    let transport = resolve_transport();
+    let submodules = if submodules { "1" } else { "0" };
+    let code = transport.exec(
+        container,
+        Some(DEFAULT_CONTAINER_USER),
+        &[
             "bash",
             "-c",
             RESET_REPO_SCRIPT,
             "--",
             repo_dir,
-            refspec,
-        ])
-        .status()
-        .map_err(|err| format!("failed to reset repo {repo_dir} in {container}: {err}"))?;
-    if status.success() {
+            &refspec,
+            submodules,
+        ],
+    )?;
+    if code == 0 {
         Ok(())
     } else {
         Err(format!(
-            "failed to reset repo {repo_dir} in {container} (exit {})",
-            status.code().unwrap_or(EXIT_RUNTIME)
+            "failed to reset repo {repo_dir} in {container} (exit {code})"
         ))
     }
 }
 
+fn reset_repo_in_container_capture(
+    container: &str,
+    repo_dir: &str,
+    refspec: &str,
+    submodules: bool,
+) -> (i32, Vec<u8>, Vec<u8>) {
+    let refspec = resolve_reset_ref(container, repo_dir, refspec);
+    let transport = resolve_transport();
+    let submodules = if submodules { "1" } else { "0" };
+    transport.exec_capture(
+        container,
+        Some(DEFAULT_CONTAINER_USER),
+        &[
+            "bash",
+            "-c",
+            RESET_REPO_SCRIPT,
+            "--",
+            repo_dir,
+            &refspec,
+            submodules,
+        ],
+    )
+}
+
 fn list_git_repos_in_container(
     container: &str,
     root: &str,
     depth: u32,
 ) -> Result<Vec<String>, String> {
-    let output = Command::new("docker")
-        .args([
-            "exec",
-            "-u",
-            DEFAULT_CONTAINER_USER,
-            container,
-            "bash",
-            "-c",
-            LIST_GIT_REPOS_SCRIPT,
-            "--",
-            root,
-            &depth.to_string(),
-        ])
-        .output()
-        .map_err(|err| format!("failed to list git repos in {container}: {err}"))?;
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    let depth = depth.to_string();
+    let (code, stdout, stderr) = resolve_runtime().exec_capture(
+        container,
+        Some(DEFAULT_CONTAINER_USER),
+        &["bash", "-c", LIST_GIT_REPOS_SCRIPT, "--", root, &depth],
+    );
+    if code != 0 {
+        let stderr = String::from_utf8_lossy(&stderr).trim().to_string();
         return Err(format!(
-            "failed to list git repos in {container} (exit {}): {stderr}",
-            output.status.code().unwrap_or(EXIT_RUNTIME)
+            "failed to list git repos in {container} (exit {code}): {stderr}"
         ));
     }
-    Ok(String::from_utf8_lossy(&output.stdout)
+    Ok(String::from_utf8_lossy(&stdout)
         .lines()
         .map(str::trim)
         .filter(|line| !line.is_empty())
@@ -2167,30 +5142,19 @@ fn list_git_repos_in_container(
 }
 
 fn container_has_git_repo(container: &str, repo_dir: &str) -> Result<bool, String> {
-    let status = Command::new("docker")
-        .args([
-            "exec",
-            "-u",
-            DEFAULT_CONTAINER_USER,
-            container,
-            "bash",
-            "-lc",
-            "test -d \"$1/.git\"",
-            "--",
-            repo_dir,
-        ])
-        .status()
-        .map_err(|err| format!("failed to inspect repo path {repo_dir} in {container}: {err}"))?;
-    Ok(status.success())
+    let code = resolve_runtime().exec(
+        container,
+        Some(DEFAULT_CONTAINER_USER),
+        &["bash", "-lc", "test -d \"$1/.git\"", "--", repo_dir],
+    )?;
+    Ok(code == 0)
 }
 
 fn detect_private_repo_dir(container: &str) -> Result<Option<String>, String> {
-    let output = Command::new("docker")
-        .args([
-            "exec",
-            "-u",
-            DEFAULT_CONTAINER_USER,
-            container,
+    let (code, stdout, stderr) = resolve_runtime().exec_capture(
+        container,
+        Some(DEFAULT_CONTAINER_USER),
+        &[
             "bash",
             "-lc",
             r#"
@@ -2202,17 +5166,15 @@ for dir in "$HOME/.private" /home/codex/.private /home/agent/.private; do
   fi
 done
 "#,
-        ])
-        .output()
-        .map_err(|err| format!("failed to inspect private repo path in {container}: {err}"))?;
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        ],
+    );
+    if code != 0 {
+        let stderr = String::from_utf8_lossy(&stderr).trim().to_string();
         return Err(format!(
-            "failed to detect private repo path in {container} (exit {}): {stderr}",
-            output.status.code().unwrap_or(EXIT_RUNTIME)
+            "failed to detect private repo path in {container} (exit {code}): {stderr}"
         ));
     }
-    let found = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let found = String::from_utf8_lossy(&stdout).trim().to_string();
     if found.is_empty() {
         Ok(None)
     } else {
@@ -2306,6 +5268,101 @@ fn default_gpg_signing_key() -> Option<String> {
     trimmed_nonempty(String::from_utf8_lossy(&output.stdout).as_ref())
 }
 
+// Subsequence fuzzy matcher: every query char must appear in candidate order,
+// but not necessarily contiguous. Score rewards contiguous runs and matches
+// right at a word boundary (start of string, or after '-'/'_'/'/'/'.') so
+// "ws-back" ranks "agent-ws-backend" above "agent-ws-rollback".
+fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    if query.is_empty() {
+        return Some(0);
+    }
+    let query_chars: Vec<char> = query.to_ascii_lowercase().chars().collect();
+    let candidate_chars: Vec<char> = candidate.to_ascii_lowercase().chars().collect();
+
+    let mut score = 0i32;
+    let mut candidate_idx = 0usize;
+    let mut previous_matched = false;
+    for (query_pos, &query_char) in query_chars.iter().enumerate() {
+        let mut found = None;
+        let search_start = candidate_idx;
+        while candidate_idx < candidate_chars.len() {
+            if candidate_chars[candidate_idx] == query_char {
+                found = Some(candidate_idx);
+                break;
+            }
+            candidate_idx += 1;
+        }
+        let matched_idx = found?;
+
+        score += 1;
+        if previous_matched && matched_idx == search_start {
+            score += 3;
+        }
+        let is_boundary = matched_idx == 0
+            || matches!(candidate_chars[matched_idx - 1], '-' | '_' | '/' | '.');
+        if is_boundary {
+            score += 2;
+        }
+        if query_pos == 0 && matched_idx == 0 {
+            score += 2;
+        }
+
+        previous_matched = true;
+        candidate_idx += 1;
+    }
+    Some(score)
+}
+
+fn fuzzy_filter<'a>(query: &str, candidates: &'a [String]) -> Vec<(&'a str, i32)> {
+    let mut scored: Vec<(&str, i32)> = candidates
+        .iter()
+        .filter_map(|candidate| fuzzy_score(query, candidate).map(|score| (candidate.as_str(), score)))
+        .collect();
+    scored.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    scored
+}
+
+// Incremental-search picker over a small candidate list: each round re-filters
+// by the typed text (subsequence fuzzy match) and lets the user pick by
+// number, refine the filter, or accept the top hit with a blank line once a
+// filter has been typed (a blank line with no filter cancels the pick).
+fn pick_from(candidates: &[String]) -> Option<String> {
+    let mut query = String::new();
+    loop {
+        let matches = fuzzy_filter(&query, candidates);
+        if matches.is_empty() {
+            eprintln!("no matches for '{query}'");
+        } else {
+            for (position, (name, _score)) in matches.iter().enumerate().take(9) {
+                eprintln!("  {}) {name}", position + 1);
+            }
+        }
+        eprint!("select workspace [{query}]> ");
+        let _ = std::io::stderr().flush();
+
+        let mut input = String::new();
+        if std::io::stdin().read_line(&mut input).is_err() {
+            return None;
+        }
+        let input = input.trim();
+
+        if input.is_empty() {
+            if query.is_empty() {
+                return None;
+            }
+            return matches.first().map(|(name, _)| name.to_string());
+        }
+        if let Ok(choice) = input.parse::<usize>() {
+            if choice >= 1 && choice <= matches.len() {
+                return Some(matches[choice - 1].0.to_string());
+            }
+            eprintln!("no such entry: {choice}");
+            continue;
+        }
+        query = input.to_string();
+    }
+}
+
 fn confirm_or_abort(prompt: &str) -> bool {
     eprint!("{prompt}");
     let _ = std::io::stderr().flush();
@@ -2396,6 +5453,83 @@ fn forward_with_launcher_and_env_capture(
     })
 }
 
+// Like `forward_with_launcher_and_env_capture`, but tees the child's
+// stdout/stderr to the parent's own streams as it arrives instead of
+// buffering silently until the child exits. `create` uses this for live
+// progress (`exec` never goes through the launcher: it runs `docker exec`
+// directly with the terminal attached); callers that only need the bytes (e.g. `rm`'s parallel job
+// pool, where concurrent children can't share a terminal) should keep
+// using the plain captured variant.
+fn forward_with_launcher_and_env_stream(
+    launcher: &Path,
+    subcommand: &str,
+    args: &[OsString],
+    env_overrides: &[(&str, &str)],
+) -> Result<CapturedForward, String> {
+    if !launcher.is_file() {
+        return Err(format!(
+            "error: launcher not found: {}\nhint: set {LAUNCHER_ENV} to the low-level launcher path",
+            launcher.display()
+        ));
+    }
+
+    let mut cmd = Command::new(launcher);
+    cmd.arg(subcommand);
+    cmd.args(args.iter().cloned());
+    for (k, v) in env_overrides {
+        cmd.env(k, v);
+    }
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::piped());
+
+    let mut child = cmd.spawn().map_err(|err| {
+        format!(
+            "error: failed to run launcher {}: {err}",
+            launcher.display()
+        )
+    })?;
+
+    let stdout_pipe = child.stdout.take().expect("stdout was piped");
+    let stderr_pipe = child.stderr.take().expect("stderr was piped");
+    let stdout_thread = std::thread::spawn(move || tee_stream(stdout_pipe, std::io::stdout()));
+    let stderr_thread = std::thread::spawn(move || tee_stream(stderr_pipe, std::io::stderr()));
+
+    let status = child.wait().map_err(|err| {
+        format!(
+            "error: failed to wait on launcher {}: {err}",
+            launcher.display()
+        )
+    })?;
+    let stdout = stdout_thread.join().unwrap_or_default();
+    let stderr = stderr_thread.join().unwrap_or_default();
+
+    Ok(CapturedForward {
+        exit_code: status.code().unwrap_or(EXIT_RUNTIME),
+        stdout,
+        stderr,
+    })
+}
+
+// Copies `reader` to `writer` a chunk at a time, flushing each chunk
+// immediately for live progress, while also accumulating everything read
+// into the buffer this returns.
+fn tee_stream<R: std::io::Read, W: Write>(mut reader: R, mut writer: W) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 8192];
+    loop {
+        match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(n) => {
+                let _ = writer.write_all(&chunk[..n]);
+                let _ = writer.flush();
+                buffer.extend_from_slice(&chunk[..n]);
+            }
+            Err(_) => break,
+        }
+    }
+    buffer
+}
+
 fn resolve_launcher_path() -> PathBuf {
     launcher_path_from_env(std::env::var_os(LAUNCHER_ENV))
 }
@@ -2421,9 +5555,12 @@ mod tests {
     use std::path::PathBuf;
 
     use super::{
-        DEFAULT_LAUNCHER_PATH, forward_with_launcher_and_env, launcher_path_from_env,
-        normalize_workspace_name_for_create, parse_auth_args, parse_create_args, parse_exec_args,
-        parse_reset_repo_args, parse_rm_args, workspace_name_variants,
+        ALIAS_CONFIG_ENV, CRED_STORE_ENV, CfgExpr, DEFAULT_LAUNCHER_PATH, HostCfg, SealedRecord,
+        eval_cfg_expr, expand_alias, forward_with_launcher_and_env, launcher_path_from_env,
+        levenshtein, normalize_workspace_name_for_create, open_credential_bytes, parse_auth_args,
+        parse_cfg_expr, parse_create_args, parse_exec_args, parse_reset_repo_args, parse_rm_args,
+        read_credential_records, seal_credential_bytes, suggest_subcommand,
+        workspace_name_variants, write_credential_records,
     };
     use crate::EXIT_RUNTIME;
 
@@ -2677,6 +5814,201 @@ mod tests {
         assert!(parsed.yes);
     }
 
+    #[test]
+    fn credential_seal_and_open_round_trip() {
+        let plaintext = b"ghp_example_token";
+        let (salt, nonce, ciphertext) = seal_credential_bytes("hunter2", plaintext).expect("seal");
+        let record = SealedRecord {
+            provider: String::from("github"),
+            host: String::from("github.com"),
+            profile: String::new(),
+            salt,
+            nonce,
+            ciphertext,
+        };
+        let opened = open_credential_bytes("hunter2", &record).expect("open");
+        assert_eq!(opened, plaintext);
+    }
+
+    #[test]
+    fn credential_open_rejects_wrong_passphrase() {
+        let (salt, nonce, ciphertext) =
+            seal_credential_bytes("correct-horse", b"secret-token").expect("seal");
+        let record = SealedRecord {
+            provider: String::from("github"),
+            host: String::from("github.com"),
+            profile: String::new(),
+            salt,
+            nonce,
+            ciphertext,
+        };
+        assert!(open_credential_bytes("wrong-password", &record).is_err());
+    }
+
+    #[test]
+    fn credential_records_round_trip_through_store_file() {
+        let temp = tempfile::TempDir::new().expect("tempdir");
+        let store_path = temp.path().join("credentials");
+        unsafe {
+            std::env::set_var(CRED_STORE_ENV, &store_path);
+        }
+
+        let (salt, nonce, ciphertext) =
+            seal_credential_bytes("hunter2", b"ghp_example_token").expect("seal");
+        let records = vec![SealedRecord {
+            provider: String::from("github"),
+            host: String::from("github.com"),
+            profile: String::from("work"),
+            salt,
+            nonce,
+            ciphertext,
+        }];
+        write_credential_records(&records).expect("write");
+        let read_back = read_credential_records().expect("read");
+
+        unsafe {
+            std::env::remove_var(CRED_STORE_ENV);
+        }
+
+        assert_eq!(read_back.len(), 1);
+        assert_eq!(read_back[0].provider, "github");
+        assert_eq!(read_back[0].host, "github.com");
+        assert_eq!(read_back[0].profile, "work");
+        assert_eq!(read_back[0].ciphertext, records[0].ciphertext);
+    }
+
+    #[test]
+    fn cfg_expr_parses_plain_ident() {
+        let expr = parse_cfg_expr("unix").expect("parse");
+        assert!(matches!(expr, CfgExpr::Ident(name) if name == "unix"));
+    }
+
+    #[test]
+    fn cfg_expr_parses_key_value_equality() {
+        let expr = parse_cfg_expr("target_os = \"linux\"").expect("parse");
+        assert!(matches!(expr, CfgExpr::Equal(key, value) if key == "target_os" && value == "linux"));
+    }
+
+    #[test]
+    fn cfg_expr_parses_nested_combinators() {
+        let expr = parse_cfg_expr("all(unix, not(any(windows, target_arch = \"wasm32\")))")
+            .expect("parse");
+        assert!(matches!(expr, CfgExpr::All(_)));
+    }
+
+    #[test]
+    fn cfg_expr_rejects_unterminated_string() {
+        let err = parse_cfg_expr("target_os = \"linux").expect_err("expected error");
+        assert!(err.contains("unterminated string"));
+    }
+
+    #[test]
+    fn eval_cfg_expr_matches_against_host_predicates() {
+        let mut idents = std::collections::HashSet::new();
+        idents.insert("unix");
+        let mut keys = std::collections::HashMap::new();
+        keys.insert("target_os", "linux");
+        let host = HostCfg { idents, keys };
+
+        assert!(eval_cfg_expr(&parse_cfg_expr("unix").expect("parse"), &host));
+        assert!(!eval_cfg_expr(&parse_cfg_expr("windows").expect("parse"), &host));
+        assert!(eval_cfg_expr(
+            &parse_cfg_expr("all(unix, target_os = \"linux\")").expect("parse"),
+            &host
+        ));
+        assert!(eval_cfg_expr(
+            &parse_cfg_expr("not(target_os = \"windows\")").expect("parse"),
+            &host
+        ));
+    }
+
+    #[test]
+    fn levenshtein_counts_edit_distance() {
+        assert_eq!(levenshtein("create", "create"), 0);
+        assert_eq!(levenshtein("reset", "rest"), 1);
+        assert_eq!(levenshtein("exec", "exce"), 2);
+    }
+
+    #[test]
+    fn suggest_subcommand_finds_close_typo() {
+        let temp = tempfile::TempDir::new().expect("tempdir");
+        let config_path = temp.path().join("config.toml");
+        unsafe {
+            std::env::set_var(ALIAS_CONFIG_ENV, &config_path);
+        }
+        let suggestion = suggest_subcommand("crete");
+        unsafe {
+            std::env::remove_var(ALIAS_CONFIG_ENV);
+        }
+        assert_eq!(suggestion.as_deref(), Some("create"));
+    }
+
+    #[test]
+    fn suggest_subcommand_stays_silent_past_threshold() {
+        let temp = tempfile::TempDir::new().expect("tempdir");
+        let config_path = temp.path().join("config.toml");
+        unsafe {
+            std::env::set_var(ALIAS_CONFIG_ENV, &config_path);
+        }
+        let suggestion = suggest_subcommand("zzzzzzzzzz");
+        unsafe {
+            std::env::remove_var(ALIAS_CONFIG_ENV);
+        }
+        assert_eq!(suggestion, None);
+    }
+
+    #[test]
+    fn expand_alias_passes_through_builtin_subcommands_unchanged() {
+        let temp = tempfile::TempDir::new().expect("tempdir");
+        let config_path = temp.path().join("config.toml");
+        unsafe {
+            std::env::set_var(ALIAS_CONFIG_ENV, &config_path);
+        }
+        let result = expand_alias("create", &[OsString::from("--yes")]);
+        unsafe {
+            std::env::remove_var(ALIAS_CONFIG_ENV);
+        }
+        let (subcommand, args) = result.expect("expand");
+        assert_eq!(subcommand, "create");
+        assert_eq!(args, vec![OsString::from("--yes")]);
+    }
+
+    #[test]
+    fn expand_alias_expands_string_form_alias() {
+        let temp = tempfile::TempDir::new().expect("tempdir");
+        let config_path = temp.path().join("config.toml");
+        fs::write(&config_path, "[alias]\nco = \"create --no-work-repos\"\n").expect("write config");
+        unsafe {
+            std::env::set_var(ALIAS_CONFIG_ENV, &config_path);
+        }
+        let result = expand_alias("co", &[OsString::from("demo")]);
+        unsafe {
+            std::env::remove_var(ALIAS_CONFIG_ENV);
+        }
+        let (subcommand, args) = result.expect("expand");
+        assert_eq!(subcommand, "create");
+        let values: Vec<String> = args
+            .into_iter()
+            .map(|item| item.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(values, vec!["--no-work-repos", "demo"]);
+    }
+
+    #[test]
+    fn expand_alias_detects_cycles() {
+        let temp = tempfile::TempDir::new().expect("tempdir");
+        let config_path = temp.path().join("config.toml");
+        fs::write(&config_path, "[alias]\na = \"b\"\nb = \"a\"\n").expect("write config");
+        unsafe {
+            std::env::set_var(ALIAS_CONFIG_ENV, &config_path);
+        }
+        let err = expand_alias("a", &[]).expect_err("expected cycle error");
+        unsafe {
+            std::env::remove_var(ALIAS_CONFIG_ENV);
+        }
+        assert!(err.contains("alias loop detected"), "unexpected error: {err}");
+    }
+
     fn write_stub_launcher(dir: &std::path::Path) -> PathBuf {
         let path = dir.join("launcher-stub.sh");
         fs::write(&path, launcher_script()).expect("write launcher stub");